@@ -0,0 +1,112 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// A wrapper around the system allocator that keeps per-thread allocation counters.
+///
+/// It is opt-in: a binary or test crate has to install it with
+/// `#[global_allocator] static A: CountingAllocator = CountingAllocator;`
+/// otherwise `measure` reports zeros.
+pub struct CountingAllocator;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub bytes_allocated: u64,
+    pub peak_bytes: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Counters {
+    allocations: u64,
+    bytes_allocated: u64,
+    current: i64,
+    peak: i64,
+}
+
+thread_local! {
+    // const initialized and without destructors, so it is safe to use from inside the allocator
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            allocations: 0,
+            bytes_allocated: 0,
+            current: 0,
+            peak: 0,
+        })
+    };
+}
+
+fn record(allocated: usize, freed: usize) {
+    let _ = COUNTERS.try_with(|c| {
+        let mut counters = c.get();
+        if allocated > 0 {
+            counters.allocations += 1;
+            counters.bytes_allocated += allocated as u64;
+        }
+        counters.current += allocated as i64 - freed as i64;
+        counters.peak = counters.peak.max(counters.current);
+        c.set(counters);
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        record(0, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            record(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Runs `f` and returns what it allocated on the current thread.
+/// The peak is measured relative to the memory in use when `f` starts.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    let before = COUNTERS.with(|c| {
+        let mut counters = c.get();
+        let before = counters;
+        counters.peak = counters.current;
+        c.set(counters);
+        before
+    });
+
+    let res = f();
+
+    let after = COUNTERS.with(|c| {
+        let mut counters = c.get();
+        let after = counters;
+        // keep the peak of an enclosing measurement intact
+        counters.peak = counters.peak.max(before.peak);
+        c.set(counters);
+        after
+    });
+
+    let stats = AllocStats {
+        allocations: after.allocations - before.allocations,
+        bytes_allocated: after.bytes_allocated - before.bytes_allocated,
+        peak_bytes: (after.peak - before.current).max(0) as u64,
+    };
+    (res, stats)
+}
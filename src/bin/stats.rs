@@ -0,0 +1,99 @@
+use std::{env, fs, path::PathBuf, process};
+
+use advent23::{
+    alloc_stats::CountingAllocator,
+    profile::{Report, profile},
+    runner::{DAYS, Part},
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn fail(msg: String) -> ! {
+    eprintln!("{}", msg);
+    process::exit(2);
+}
+
+fn main() {
+    let mut inputs = PathBuf::from("tests");
+    let mut save: Option<String> = None;
+    let mut baseline: Option<String> = None;
+    let mut days: Vec<u8> = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--inputs" => {
+                inputs = args
+                    .next()
+                    .unwrap_or_else(|| fail("missing directory".into()))
+                    .into()
+            }
+            "--save" => save = Some(args.next().unwrap_or_else(|| fail("missing file".into()))),
+            "--baseline" => {
+                baseline = Some(args.next().unwrap_or_else(|| fail("missing file".into())))
+            }
+            _ => match arg.trim_start_matches('d').parse() {
+                Ok(day) if DAYS.contains(&day) => days.push(day),
+                _ => fail(format!(
+                    "usage: stats [--inputs DIR] [--save FILE] [--baseline FILE] [day]...\ninvalid argument: {}",
+                    arg
+                )),
+            },
+        }
+    }
+    if days.is_empty() {
+        days = DAYS.collect();
+    }
+
+    let mut report = Report::default();
+
+    println!(
+        "{:>3} {:>4} {:>10} {:>12} {:>12} {:>15}",
+        "day", "part", "time (ms)", "peak (KiB)", "allocations", "allocated (KiB)"
+    );
+    for day in days {
+        let path = inputs.join(format!("d{}", day)).join("in2.txt");
+        let input = fs::read_to_string(&path)
+            .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+
+        let parts: &[Part] = if day == 25 {
+            &[Part::One]
+        } else {
+            &[Part::One, Part::Two]
+        };
+        for &part in parts {
+            let (answer, m) = profile(day, part, &input);
+            if let Err(e) = answer {
+                eprintln!("day {} {} failed: {}", day, part, e);
+            }
+            println!(
+                "{:>3} {:>4} {:>10.1} {:>12} {:>12} {:>15}",
+                day,
+                if part == Part::One { 1 } else { 2 },
+                m.time.as_secs_f64() * 1000.0,
+                m.alloc.peak_bytes / 1024,
+                m.alloc.allocations,
+                m.alloc.bytes_allocated / 1024,
+            );
+            report.measurements.push(m);
+        }
+    }
+
+    if let Some(path) = save {
+        fs::write(&path, report.to_string()).unwrap_or_else(|e| fail(format!("{}: {}", path, e)));
+    }
+
+    if let Some(path) = baseline {
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| fail(format!("{}: {}", path, e)));
+        let baseline = Report::parse(&text).unwrap_or_else(|e| fail(format!("{}: {}", path, e)));
+
+        let regressions = report.regressions(&baseline, 0.2);
+        for r in regressions.iter() {
+            println!("regression: {}", r);
+        }
+        if !regressions.is_empty() {
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::{env, process, time::Duration};
+
+use advent23::{
+    runner::Part,
+    watch::{watch, Watcher},
+};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("usage: watch <day> <input file> [part]...");
+        process::exit(2);
+    }
+
+    let day: u8 = match args[0].trim_start_matches('d').parse() {
+        Ok(day) => day,
+        Err(_) => {
+            eprintln!("invalid day: {}", args[0]);
+            process::exit(2);
+        }
+    };
+
+    let mut parts = Vec::new();
+    for s in args[2..].iter() {
+        match Part::try_from(s.as_str()) {
+            Ok(p) => parts.push(p),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        }
+    }
+    if parts.is_empty() {
+        parts.push(Part::One);
+        if day != 25 {
+            parts.push(Part::Two);
+        }
+    }
+
+    let mut watcher = Watcher::new(&args[1], day, parts);
+    if let Err(e) = watch(&mut watcher, Duration::from_millis(500)) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fmt::{Debug, Display},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Can be cloned and handed to another thread, which can then stop a running solver.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits how long a solver may run, by a deadline, a cancel token, a number of checks or any
+/// combination of them.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    token: Option<CancelToken>,
+    max_checks: Option<u64>,
+    checks: Arc<AtomicU64>, // shared between clones, like the token
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    pub fn with_token(self, token: CancelToken) -> Self {
+        Self {
+            token: Some(token),
+            ..self
+        }
+    }
+
+    /// Lets `check` pass `max_checks` times and fail after that, so tests can stop a solver at
+    /// the same point on every machine.
+    pub fn with_max_checks(self, max_checks: u64) -> Self {
+        Self {
+            max_checks: Some(max_checks),
+            ..self
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.token.as_ref().is_some_and(|t| t.is_cancelled())
+            || self.deadline.is_some_and(|d| Instant::now() >= d)
+            || self.max_checks.is_some_and(|m| self.checks.load(Ordering::Relaxed) >= m)
+    }
+
+    /// Meant to be called inside the main loop of a solver. The partial result is only computed on expiry.
+    /// Reading the clock isn't free, so very hot loops should only call it every so often.
+    pub fn check<P>(&self, partial: impl FnOnce() -> P) -> Result<(), TimedOut<P>> {
+        if self.expired() {
+            Err(TimedOut { partial: partial() })
+        } else {
+            self.checks.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+}
+
+/// Returned by solvers that ran out of budget, together with whatever progress they had made.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimedOut<P> {
+    pub partial: P,
+}
+
+impl<P: Debug> Display for TimedOut<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timed out (partial result: {:?})", self.partial)
+    }
+}
@@ -1,47 +1,354 @@
-fn extract_value(s: &str, allow_words: bool) -> u32 {
-    if allow_words {
-        let mut lo_idx = s.len();
-        let mut lo = 0;
-        let mut hi_idx = -1;
-        let mut hi = 0;
-
-        let numbers: Vec<_> = [
-            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-        ]
-        .into_iter()
-        .enumerate()
-        .map(|(i, s)| (i + 1, s.to_string()))
-        .chain((0..=9).map(|n| (n, format!("{}", n))))
-        .collect();
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+};
+
+/// Aho-Corasick automaton, which finds all (possibly overlapping) tokens in a single pass.
+struct Automaton {
+    next: Vec<HashMap<u8, usize>>,
+    fail: Vec<usize>,
+    outputs: Vec<Vec<usize>>, // indices of the tokens that end in this state
+    lengths: Vec<usize>,      // length of every token in bytes
+}
+
+impl Automaton {
+    fn new<'a>(patterns: impl Iterator<Item = &'a [u8]>) -> Self {
+        let mut next = vec![HashMap::new()];
+        let mut outputs = vec![Vec::new()];
+        let mut lengths = Vec::new();
 
-        for (n, w) in numbers {
-            if let Some(idx) = s.find(&w)
-                && idx < lo_idx
-            {
-                lo_idx = idx;
-                lo = n;
+        for (token_idx, pattern) in patterns.enumerate() {
+            lengths.push(pattern.len());
+            let mut state = 0;
+            for &b in pattern {
+                state = match next[state].get(&b) {
+                    Some(&n) => n,
+                    None => {
+                        let n = next.len();
+                        next.push(HashMap::new());
+                        outputs.push(Vec::new());
+                        next[state].insert(b, n);
+                        n
+                    }
+                };
             }
-            if let Some(idx) = s.rfind(&w)
-                && idx as i32 > hi_idx
-            {
-                hi_idx = idx as i32;
-                hi = n;
+            outputs[state].push(token_idx);
+        }
+
+        // breadth first, so that the fail state of the parent is always known
+        let mut fail = vec![0; next.len()];
+        let mut q: VecDeque<usize> = next[0].values().copied().collect();
+        while let Some(state) = q.pop_front() {
+            for (&b, &child) in next[state].clone().iter() {
+                let mut f = fail[state];
+                while f != 0 && !next[f].contains_key(&b) {
+                    f = fail[f];
+                }
+                fail[child] = next[f].get(&b).copied().filter(|&n| n != child).unwrap_or(0);
+
+                let inherited = outputs[fail[child]].clone();
+                outputs[child].extend(inherited);
+                q.push_back(child);
             }
         }
 
-        (lo * 10 + hi) as u32
-    } else {
-        let mut v = 0;
-        v += (s.chars().find(|c| c.is_numeric()).unwrap() as u32) - ('0' as u32);
-        v *= 10;
-        v += (s.chars().rfind(|c| c.is_numeric()).unwrap() as u32) - ('0' as u32);
-        v
+        Automaton {
+            next,
+            fail,
+            outputs,
+            lengths,
+        }
+    }
+
+    fn step(&self, mut state: usize, b: u8) -> usize {
+        loop {
+            if let Some(&n) = self.next[state].get(&b) {
+                return n;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.fail[state];
+        }
+    }
+}
+
+/// The words (and digits) that count as digits when decoding calibration values.
+pub struct Vocabulary {
+    tokens: Vec<(String, Vec<u8>)>, // token and the digits it stands for
+    ignore_case: bool,
+    automaton: Automaton,
+}
+
+const WORDS_EN: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const WORDS_SV: [&str; 9] = [
+    "ett", "två", "tre", "fyra", "fem", "sex", "sju", "åtta", "nio",
+];
+const WORDS_DE: [&str; 9] = [
+    "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const WORDS_FR: [&str; 9] = [
+    "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const WORDS_ES: [&str; 9] = [
+    "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+
+impl Vocabulary {
+    /// Each token maps to a string of digits, e.g. `("twelve", "12")`.
+    pub fn new(tokens: &[(&str, &str)]) -> Result<Self, String> {
+        let mut parsed = Vec::new();
+        for &(token, digits) in tokens {
+            if token.is_empty() {
+                return Err("empty token".to_string());
+            }
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("invalid digits for {}: {}", token, digits));
+            }
+            parsed.push((token.to_string(), digits.bytes().map(|b| b - b'0').collect()));
+        }
+        Ok(Self::from_tokens(parsed, false))
+    }
+
+    fn from_tokens(tokens: Vec<(String, Vec<u8>)>, ignore_case: bool) -> Self {
+        let patterns: Vec<String> = tokens
+            .iter()
+            .map(|(t, _)| if ignore_case { t.to_lowercase() } else { t.clone() })
+            .collect();
+        let automaton = Automaton::new(patterns.iter().map(|p| p.as_bytes()));
+        Self {
+            tokens,
+            ignore_case,
+            automaton,
+        }
+    }
+
+    /// Only the digits 0 to 9.
+    pub fn digits() -> Self {
+        Self::with_words(&[])
+    }
+
+    /// The digits and the words "one" to "nine", as in part 2 of the puzzle.
+    pub fn english() -> Self {
+        Self::with_words(&WORDS_EN)
+    }
+
+    /// Supported locales are "en", "sv", "de", "fr" and "es".
+    pub fn for_locale(locale: &str) -> Result<Self, String> {
+        match locale {
+            "en" => Ok(Self::with_words(&WORDS_EN)),
+            "sv" => Ok(Self::with_words(&WORDS_SV)),
+            "de" => Ok(Self::with_words(&WORDS_DE)),
+            "fr" => Ok(Self::with_words(&WORDS_FR)),
+            "es" => Ok(Self::with_words(&WORDS_ES)),
+            _ => Err(format!("unknown locale: {}", locale)),
+        }
+    }
+
+    /// The digits plus the given words for one to nine.
+    fn with_words(words: &[&str]) -> Self {
+        let tokens = (0..=9)
+            .map(|n| (n.to_string(), vec![n]))
+            .chain(
+                words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| (w.to_string(), vec![i as u8 + 1])),
+            )
+            .collect();
+        Self::from_tokens(tokens, false)
+    }
+
+    pub fn ignore_case(self) -> Self {
+        Self::from_tokens(self.tokens, true)
+    }
+
+    /// The span in `s` and the index of every token found, ordered by position.
+    fn find_tokens(&self, s: &str) -> Vec<(Range<usize>, usize)> {
+        // the bytes the automaton sees, and the position in `s` each of them came from
+        let (bytes, origins): (Vec<u8>, Vec<usize>) = if self.ignore_case {
+            let mut buf = [0; 4];
+            s.char_indices()
+                .flat_map(|(pos, c)| c.to_lowercase().map(move |lc| (pos, lc)))
+                .flat_map(|(pos, lc)| {
+                    let encoded = lc.encode_utf8(&mut buf).as_bytes().to_vec();
+                    encoded.into_iter().map(move |b| (b, pos))
+                })
+                .unzip()
+        } else {
+            s.bytes().enumerate().map(|(pos, b)| (b, pos)).unzip()
+        };
+
+        let mut found = Vec::new();
+        let mut state = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            state = self.automaton.step(state, b);
+            for &t in self.automaton.outputs[state].iter() {
+                let start = origins[i + 1 - self.automaton.lengths[t]];
+                let end = if self.ignore_case {
+                    origins[i] + s[origins[i]..].chars().next().unwrap().len_utf8()
+                } else {
+                    i + 1
+                };
+                found.push((start..end, t));
+            }
+        }
+
+        found.sort_by_key(|(span, _)| span.start);
+        found
+    }
+
+    /// Every digit in the line with the byte position of the token it is part of, in order.
+    /// Overlapping tokens are all found, so "eightwo" gives both 8 and 2.
+    pub fn find_digits(&self, s: &str) -> Vec<(usize, u8)> {
+        self.extract(s)
+            .map(|e| e.digits.into_iter().map(|d| (d.span.start, d.digit)).collect())
+            .unwrap_or_default()
+    }
+
+    /// All digits in the line, or an error if there are none.
+    pub fn extract(&self, s: &str) -> Result<Extraction, String> {
+        let mut digits = Vec::new();
+        for (span, t) in self.find_tokens(s) {
+            let (token, values) = &self.tokens[t];
+            let from_word = !token.bytes().all(|b| b.is_ascii_digit());
+            for &digit in values {
+                digits.push(FoundDigit {
+                    digit,
+                    span: span.clone(),
+                    from_word,
+                });
+            }
+        }
+
+        if digits.is_empty() {
+            return Err(format!("no digits in {}", s));
+        }
+        Ok(Extraction { digits })
+    }
+
+    /// The first and last digit as a two-digit number, or `None` if there are no digits.
+    pub fn calibration_value(&self, s: &str) -> Option<u32> {
+        let value = self.extract(s).ok()?.value(Combination::FirstLast).ok()?;
+        Some(value as u32)
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FoundDigit {
+    pub digit: u8,
+    pub span: Range<usize>, // bytes of the token, which may stand for several digits
+    pub from_word: bool,
+}
+
+/// The digits found in one line, never empty.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Extraction {
+    pub digits: Vec<FoundDigit>,
+}
+
+impl Extraction {
+    pub fn value(&self, rule: Combination) -> Result<u64, String> {
+        let digits: Vec<u8> = self.digits.iter().map(|d| d.digit).collect();
+        rule.combine(&digits)
+    }
+}
+
+/// How the digits of a line are turned into its calibration value.
+#[derive(Debug, Clone, Copy)]
+pub enum Combination {
+    /// The first and last digit as a two-digit number, as in the puzzle.
+    FirstLast,
+    /// All digits in order as one number.
+    Concatenated,
+    Sum,
+    Custom(fn(&[u8]) -> Result<u64, String>),
+}
+
+impl Combination {
+    pub fn combine(self, digits: &[u8]) -> Result<u64, String> {
+        let (&first, &last) = digits.first().zip(digits.last()).ok_or("no digits")?;
+        match self {
+            Combination::FirstLast => Ok(first as u64 * 10 + last as u64),
+            Combination::Concatenated => digits.iter().try_fold(0u64, |acc, &d| {
+                acc.checked_mul(10)
+                    .and_then(|v| v.checked_add(d as u64))
+                    .ok_or_else(|| "too many digits".to_string())
+            }),
+            Combination::Sum => Ok(digits.iter().map(|&d| d as u64).sum()),
+            Combination::Custom(f) => f(digits),
+        }
+    }
+}
+
+/// Sums the values of all lines, or reports the first line (1-based) that has no value.
+pub fn try_run(lines: &[&str], vocabulary: &Vocabulary, rule: Combination) -> Result<u64, String> {
+    let mut sum: u64 = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let value = vocabulary
+            .extract(line)
+            .and_then(|e| e.value(rule))
+            .map_err(|e| format!("line {}: {}", i + 1, e))?;
+        sum = sum.checked_add(value).ok_or("sum overflowed")?;
+    }
+    Ok(sum)
+}
+
+/// Checks that the lines look like calibration values: lowercase letters and digits only.
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no lines".to_string());
+    }
+    for line in lines {
+        if line.is_empty() {
+            return Err("empty line".to_string());
+        }
+        if let Some(c) = line
+            .chars()
+            .find(|c| !c.is_ascii_lowercase() && !c.is_ascii_digit())
+        {
+            return Err(format!("invalid character: {}", c));
+        }
+    }
+    if !lines.iter().any(|line| line.chars().any(|c| c.is_ascii_lowercase())) {
+        return Err("no letters in input".to_string());
+    }
+    Ok(())
+}
+
 pub fn run(lines: Vec<&str>, allow_words: bool) -> u32 {
-    lines
-        .into_iter()
-        .map(|s| extract_value(s, allow_words))
-        .sum()
+    if allow_words {
+        run_with_vocabulary(lines, &Vocabulary::english())
+    } else {
+        run_with_vocabulary(lines, &Vocabulary::digits())
+    }
+}
+
+pub fn run_with_vocabulary(lines: Vec<&str>, vocabulary: &Vocabulary) -> u32 {
+    try_run(&lines, vocabulary, Combination::FirstLast).unwrap() as u32
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let values = |vocabulary: &Vocabulary| {
+        lines
+            .iter()
+            .filter_map(|line| {
+                let value = vocabulary.calibration_value(line)?;
+                Some(format!("{} -> {}\n", line, value))
+            })
+            .collect::<String>()
+    };
+    let english = Vocabulary::english();
+    let digits = lines
+        .iter()
+        .map(|line| format!("{} -> {:?}\n", line, english.find_digits(line)))
+        .collect();
+
+    vec![
+        ("values_digits", values(&Vocabulary::digits())),
+        ("values_words", values(&english)),
+        ("digits_words", digits),
+    ]
 }
@@ -2,8 +2,10 @@ use std::fmt::{Display, Write};
 
 use queues::{IsQueue, Queue};
 
+use crate::rng::Rng;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Dir {
+pub enum Dir {
     Up,
     Down,
     Left,
@@ -120,11 +122,13 @@ enum Coloring {
     Outside,
 }
 
+#[derive(Clone)]
 struct Spot {
     pipe: Option<Pipe>,
     coloring: Option<Coloring>,
 }
 
+#[derive(Clone)]
 struct Grid {
     spots: Vec<Vec<Spot>>,
     // TODO: store spot category here (Path, Left, Right) which will later be used to flood fill and finally decide which of Left and Right is Inside and Outside
@@ -190,45 +194,71 @@ impl Grid {
         }
     }
 
-    fn neighbor_pipe(&self, x: usize, y: usize, dir: Dir) -> Option<Pipe> {
-        self.neighbor_coords(x, y, dir)
-            .and_then(|(nx, ny)| self.spots[ny][nx].pipe)
-    }
-
     fn coloring_mut(&mut self, x: usize, y: usize) -> &mut Option<Coloring> {
         &mut self.spots[y][x].coloring
     }
 
-    fn infer_pipe(&mut self, sx: usize, sy: usize) -> Result<(), String> {
-        let mut dirs = Vec::with_capacity(4);
+    /// The tile that the pipe at `(x, y)` connects to in `dir`, if the pipe there connects back.
+    fn follow(&self, x: usize, y: usize, dir: Dir) -> Option<(usize, usize)> {
+        let (nx, ny) = self.neighbor_coords(x, y, dir)?;
+        let pipe = self.spots[ny][nx].pipe?;
+        pipe.connects_to(dir.opposite()).then_some((nx, ny))
+    }
 
-        if let Some(pipe) = self.neighbor_pipe(sx, sy, Dir::Left)
-            && pipe.connects_to(Dir::Right)
-        {
-            dirs.push(Dir::Left);
-        }
-        if let Some(pipe) = self.neighbor_pipe(sx, sy, Dir::Right)
-            && pipe.connects_to(Dir::Left)
-        {
-            dirs.push(Dir::Right);
-        }
-        if let Some(pipe) = self.neighbor_pipe(sx, sy, Dir::Up)
-            && pipe.connects_to(Dir::Down)
-        {
-            dirs.push(Dir::Up);
+    /// Follows the pipes from `(x, y)`, returning the tiles and whether they get back to it.
+    fn trace(&self, x: usize, y: usize) -> (Vec<(usize, usize)>, bool) {
+        let mut tiles = vec![(x, y)];
+        let Some(pipe) = self.spots[y][x].pipe else {
+            return (tiles, false);
+        };
+        let mut dir = pipe.dirs().0;
+        let (mut cx, mut cy) = (x, y);
+        loop {
+            let Some((nx, ny)) = self.follow(cx, cy, dir) else {
+                return (tiles, false);
+            };
+            if (nx, ny) == (x, y) {
+                return (tiles, true);
+            }
+            tiles.push((nx, ny));
+            let (d1, d2) = self.spots[ny][nx].pipe.unwrap().dirs();
+            dir = if d1 == dir.opposite() { d2 } else { d1 };
+            (cx, cy) = (nx, ny);
         }
-        if let Some(pipe) = self.neighbor_pipe(sx, sy, Dir::Down)
-            && pipe.connects_to(Dir::Up)
-        {
-            dirs.push(Dir::Down);
+    }
+
+    /// The pipes that would connect to two neighbours at the start position.
+    fn start_candidates(&self, sx: usize, sy: usize) -> Vec<Pipe> {
+        use Pipe::*;
+
+        [Vertical, Horizontal, UpLeft, UpRight, DownLeft, DownRight]
+            .into_iter()
+            .filter(|p| {
+                let (d1, d2) = p.dirs();
+                self.follow(sx, sy, d1).is_some() && self.follow(sx, sy, d2).is_some()
+            })
+            .collect()
+    }
+
+    /// Uses the only pipe that connects to two neighbours, or else the only one of those that
+    /// closes a loop.
+    fn infer_pipe(&mut self, sx: usize, sy: usize) -> Result<(), String> {
+        let mut candidates = self.start_candidates(sx, sy);
+        if candidates.len() > 1 {
+            candidates.retain(|&pipe| {
+                self.spots[sy][sx].pipe = Some(pipe);
+                self.trace(sx, sy).1
+            });
+            self.spots[sy][sx].pipe = None;
         }
-        if dirs.len() != 2 {
+        if candidates.len() != 1 {
+            let chars: String = candidates.into_iter().map(char::from).collect();
             return Err(format!(
-                "Wrong number of pipes connecting. Got these: {:?}",
-                dirs
+                "expected exactly one pipe at the start that closes a loop, got {:?}",
+                chars
             ));
         }
-        self.spots[sy][sx].pipe = Some(Pipe::try_from((dirs[0], dirs[1])).unwrap());
+        self.spots[sy][sx].pipe = Some(candidates[0]);
 
         Ok(())
     }
@@ -356,16 +386,358 @@ impl Grid {
     }
 }
 
-fn parse_grid(lines: Vec<&str>) -> (Grid, (usize, usize)) {
-    let mut sx = 0;
-    let mut sy = 0;
+/// A closed loop of pipes, in the order they are connected, starting with the topmost of the
+/// leftmost tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeLoop {
+    pub tiles: Vec<(usize, usize)>,
+}
+
+impl PipeLoop {
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The area of the polygon through the centres of the tiles, by the shoelace formula.
+    pub fn shoelace_area(&self) -> u64 {
+        let twice: i64 = self
+            .tiles
+            .iter()
+            .zip(self.tiles.iter().cycle().skip(1))
+            .map(|(&(x1, y1), &(x2, y2))| x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64)
+            .sum();
+        twice.unsigned_abs() / 2
+    }
+
+    /// The number of tiles inside the loop, by Pick's theorem: `A = I + B / 2 - 1`.
+    pub fn inside_by_pick(&self) -> u64 {
+        self.shoelace_area() + 1 - self.tiles.len() as u64 / 2
+    }
+}
+
+/// A pipe end that doesn't connect to anything, the position is that of the pipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipeIssue {
+    /// The pipe points outside the grid or at the ground in direction `dir`.
+    Dangling {
+        at: (usize, usize),
+        pipe: char,
+        dir: Dir,
+    },
+    /// The pipe points at a pipe that doesn't point back.
+    Broken {
+        at: (usize, usize),
+        pipe: char,
+        neighbor: (usize, usize),
+    },
+}
+
+/// A way to read the start position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartPipe {
+    pub pipe: char,
+    pub length: usize,
+    pub inside: u64,
+}
+
+/// Any grid of pipes, which doesn't need to have a start position or a single loop.
+pub struct Maze {
+    grid: Grid,
+    start: Option<(usize, usize)>,
+}
+
+impl Display for Maze {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.grid.spots.iter().enumerate() {
+            for (x, spot) in row.iter().enumerate() {
+                let ch = match spot.pipe {
+                    _ if self.start == Some((x, y)) => 'S',
+                    Some(pipe) => pipe.into(),
+                    None => '.',
+                };
+                f.write_char(ch)?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl Maze {
+    /// The pipe under the start position is inferred when only one of them closes a loop,
+    /// see [`Maze::start_pipes`] otherwise.
+    pub fn parse(lines: &[&str]) -> Result<Maze, String> {
+        let (mut grid, start) = parse_spots(lines)?;
+        if grid.spots.is_empty() || grid.spots[0].is_empty() {
+            return Err("empty grid".to_string());
+        }
+        if let Some((sx, sy)) = start {
+            let _ = grid.infer_pipe(sx, sy);
+        }
+        Ok(Maze { grid, start })
+    }
+
+    pub fn start(&self) -> Option<(usize, usize)> {
+        self.start
+    }
+
+    /// Every closed loop of pipes, ordered by their first tile.
+    pub fn loops(&self) -> Vec<PipeLoop> {
+        let spots = &self.grid.spots;
+        let mut seen = vec![vec![false; spots[0].len()]; spots.len()];
+        let mut loops = Vec::new();
+
+        // going column by column finds the topmost of the leftmost tiles of a loop first
+        for x in 0..spots[0].len() {
+            for y in 0..spots.len() {
+                if spots[y][x].pipe.is_none() || seen[y][x] {
+                    continue;
+                }
+
+                let (tiles, closed) = self.grid.trace(x, y);
+                for &(tx, ty) in tiles.iter() {
+                    seen[ty][tx] = true;
+                }
+                // the tiles of an open chain might continue on the other side of the first tile,
+                // but those aren't part of a loop either
+                if closed {
+                    loops.push(PipeLoop { tiles });
+                }
+            }
+        }
+        loops
+    }
+
+    /// The loop going through the start position, if there is one.
+    pub fn start_loop(&self) -> Option<PipeLoop> {
+        let start = self.start?;
+        self.loops().into_iter().find(|l| l.tiles.contains(&start))
+    }
+
+    /// Every pipe that fits at the start position and closes a loop, with the length of that
+    /// loop and the number of tiles inside it.
+    pub fn start_pipes(&self) -> Result<Vec<StartPipe>, String> {
+        let (sx, sy) = self.start.ok_or("no start position")?;
+        let mut found = Vec::new();
+        for pipe in self.grid.start_candidates(sx, sy) {
+            let mut maze = Maze {
+                grid: self.grid.clone(),
+                start: self.start,
+            };
+            maze.grid.spots[sy][sx].pipe = Some(pipe);
+            let (tiles, closed) = maze.grid.trace(sx, sy);
+            if !closed {
+                continue;
+            }
+            let start_loop = maze.start_loop().unwrap();
+            found.push(StartPipe {
+                pipe: pipe.into(),
+                length: tiles.len(),
+                inside: maze.enclosed_area(&start_loop)?,
+            });
+        }
+        Ok(found)
+    }
+
+    /// Every pipe end that doesn't connect to a pipe pointing back, sorted by position.
+    pub fn issues(&self) -> Vec<PipeIssue> {
+        let mut issues = Vec::new();
+        for (y, row) in self.grid.spots.iter().enumerate() {
+            for (x, spot) in row.iter().enumerate() {
+                let Some(pipe) = spot.pipe else {
+                    continue;
+                };
+                let (d1, d2) = pipe.dirs();
+                for dir in [d1, d2] {
+                    let at = (x, y);
+                    let pipe = pipe.into();
+                    match self.grid.neighbor_coords(x, y, dir) {
+                        Some((nx, ny)) => match self.grid.spots[ny][nx].pipe {
+                            Some(other) if other.connects_to(dir.opposite()) => (),
+                            Some(_) => issues.push(PipeIssue::Broken {
+                                at,
+                                pipe,
+                                neighbor: (nx, ny),
+                            }),
+                            None => issues.push(PipeIssue::Dangling { at, pipe, dir }),
+                        },
+                        None => issues.push(PipeIssue::Dangling { at, pipe, dir }),
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// The number of tiles inside the loop by flood filling, which must agree with Pick's theorem.
+    pub fn enclosed_area(&self, pipe_loop: &PipeLoop) -> Result<u64, String> {
+        let &(x, y) = pipe_loop.tiles.first().ok_or("empty loop")?;
+
+        let mut grid = self.grid.clone();
+        // pipes that aren't part of this loop are just tiles
+        for row in grid.spots.iter_mut() {
+            for spot in row.iter_mut() {
+                spot.pipe = None;
+            }
+        }
+        for &(tx, ty) in pipe_loop.tiles.iter() {
+            grid.spots[ty][tx].pipe = self.grid.spots[ty][tx].pipe;
+        }
+
+        let flood_fill = grid.enclosed_area(x, y) as u64;
+        let pick = pipe_loop.inside_by_pick();
+        if flood_fill != pick {
+            return Err(format!(
+                "flood fill finds {} tiles inside, Pick's theorem {}",
+                flood_fill, pick
+            ));
+        }
+        Ok(flood_fill)
+    }
+}
+
+/// A random maze with a single loop through the start position, and random pipes around it.
+/// The loop goes around a random tree, so it has tiles inside.
+pub fn generate(width: usize, height: usize, seed: u64) -> Vec<String> {
+    assert!(width >= 3 && height >= 3, "the maze must be at least 3x3");
+    let mut rng = Rng::new(seed);
+
+    // the tree has nodes on a coarse grid, and the loop goes around the squares of a fine grid
+    // covering the nodes and tree edges, each of which is two tiles wide
+    let (cw, ch) = ((width + 1) / 4, (height + 1) / 4);
+    let target = 1 + rng.below((cw * ch) as u64) as usize;
+
+    let mut in_tree = vec![vec![false; cw]; ch];
+    // edges to the right of and below a node
+    let mut right = vec![vec![false; cw]; ch];
+    let mut down = vec![vec![false; cw]; ch];
+    let first = (rng.below(cw as u64) as usize, rng.below(ch as u64) as usize);
+    in_tree[first.1][first.0] = true;
+    let mut frontier = vec![first];
+    let mut count = 1;
+    while count < target {
+        let &(x, y) = &frontier[rng.below(frontier.len() as u64) as usize];
+        let mut options = Vec::new();
+        if x > 0 && !in_tree[y][x - 1] {
+            options.push((x - 1, y));
+        }
+        if x + 1 < cw && !in_tree[y][x + 1] {
+            options.push((x + 1, y));
+        }
+        if y > 0 && !in_tree[y - 1][x] {
+            options.push((x, y - 1));
+        }
+        if y + 1 < ch && !in_tree[y + 1][x] {
+            options.push((x, y + 1));
+        }
+        if options.is_empty() {
+            frontier.retain(|&n| n != (x, y));
+            continue;
+        }
+        let (nx, ny) = options[rng.below(options.len() as u64) as usize];
+        match (nx.cmp(&x), ny.cmp(&y)) {
+            (std::cmp::Ordering::Less, _) => right[ny][nx] = true,
+            (std::cmp::Ordering::Greater, _) => right[y][x] = true,
+            (_, std::cmp::Ordering::Less) => down[ny][nx] = true,
+            _ => down[y][x] = true,
+        }
+        in_tree[ny][nx] = true;
+        frontier.push((nx, ny));
+        count += 1;
+    }
+
+    // square (a, b) of the fine grid spans the tiles (a, b) to (a + 1, b + 1)
+    let filled = |a: isize, b: isize| -> bool {
+        if a < 0 || b < 0 {
+            return false;
+        }
+        let (p, q) = (a as usize / 2, b as usize / 2);
+        let (x, y) = (p / 2, q / 2);
+        if x >= cw || y >= ch {
+            return false;
+        }
+        match (p % 2, q % 2) {
+            (0, 0) => in_tree[y][x],
+            (1, 0) => right[y][x],
+            (0, 1) => down[y][x],
+            _ => false,
+        }
+    };
+
+    const JUNK: [char; 7] = ['|', '-', 'J', 'L', '7', 'F', '.'];
+    let mut lines = Vec::with_capacity(height);
+    let mut loop_tiles = Vec::new();
+    for y in 0..height {
+        let mut line = String::with_capacity(width);
+        for x in 0..width {
+            let (a, b) = (x as isize, y as isize);
+            let (nw, ne, sw, se) = (
+                filled(a - 1, b - 1),
+                filled(a, b - 1),
+                filled(a - 1, b),
+                filled(a, b),
+            );
+            let mut dirs = Vec::new();
+            if nw != ne {
+                dirs.push(Dir::Up);
+            }
+            if sw != se {
+                dirs.push(Dir::Down);
+            }
+            if nw != sw {
+                dirs.push(Dir::Left);
+            }
+            if ne != se {
+                dirs.push(Dir::Right);
+            }
+            let ch = if dirs.len() == 2 {
+                loop_tiles.push((x, y));
+                Pipe::try_from((dirs[0], dirs[1])).unwrap().into()
+            } else {
+                JUNK[rng.below(JUNK.len() as u64) as usize]
+            };
+            line.push(ch);
+        }
+        lines.push(line);
+    }
+
+    let (sx, sy) = loop_tiles[rng.below(loop_tiles.len() as u64) as usize];
+    lines[sy].replace_range(sx..sx + 1, "S");
+    // random pipes pointing at the start would make its pipe ambiguous
+    let neighbors = [
+        (sx.wrapping_sub(1), sy),
+        (sx + 1, sy),
+        (sx, sy.wrapping_sub(1)),
+        (sx, sy + 1),
+    ];
+    for (x, y) in neighbors {
+        if x < width && y < height && !loop_tiles.contains(&(x, y)) {
+            lines[y].replace_range(x..x + 1, ".");
+        }
+    }
+    lines
+}
+
+fn parse_grid(lines: Vec<&str>) -> Result<(Grid, (usize, usize)), String> {
+    let (grid, start) = parse_spots(&lines)?;
+    Ok((grid, start.ok_or("no start position")?))
+}
+
+fn parse_spots(lines: &[&str]) -> Result<(Grid, Option<(usize, usize)>), String> {
+    let mut start = None;
     let mut rows = Vec::new();
-    for line in lines {
+    for line in lines.iter() {
         let mut row = Vec::new();
         for ch in line.chars() {
             if ch == 'S' {
-                sx = row.len();
-                sy = rows.len();
+                if start.is_some() {
+                    return Err("multiple start positions".to_string());
+                }
+                start = Some((row.len(), rows.len()));
                 row.push(Spot {
                     pipe: None,
                     coloring: None,
@@ -376,20 +748,27 @@ fn parse_grid(lines: Vec<&str>) -> (Grid, (usize, usize)) {
                     coloring: None,
                 });
             } else {
-                let pipe = Pipe::try_from(ch).unwrap();
+                let pipe = Pipe::try_from(ch)?;
                 row.push(Spot {
                     pipe: Some(pipe),
                     coloring: None,
                 });
             }
         }
+        if rows.first().is_some_and(|r: &Vec<Spot>| r.len() != row.len()) {
+            return Err("lines have different lengths".to_string());
+        }
         rows.push(row);
     }
-    (Grid { spots: rows }, (sx, sy))
+    Ok((Grid { spots: rows }, start))
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    parse_grid(lines.to_vec()).map(|_| ())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
-    let (mut grid, (sx, sy)) = parse_grid(lines);
+    let (mut grid, (sx, sy)) = parse_grid(lines).unwrap();
     grid.infer_pipe(sx, sy).unwrap();
     if part1 {
         grid.calc_cycle(sx, sy) / 2
@@ -397,3 +776,29 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
         grid.enclosed_area(sx, sy)
     }
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let maze = Maze::parse(&lines).unwrap();
+    let loops = maze
+        .loops()
+        .iter()
+        .map(|l| {
+            format!(
+                "from {:?}: length {}, shoelace area {}, inside {}\n",
+                l.tiles[0],
+                l.len(),
+                l.shoelace_area(),
+                maze.enclosed_area(l).unwrap()
+            )
+        })
+        .collect();
+    let (mut grid, (sx, sy)) = parse_grid(lines).unwrap();
+    grid.infer_pipe(sx, sy).unwrap();
+    let start = format!("({}, {}) {}\n", sx, sy, char::from(grid.spots[sy][sx].pipe.unwrap()));
+    let length = grid.calc_cycle(sx, sy);
+    vec![
+        ("start_pipe", start),
+        ("colored_grid", format!("loop length {}\n{}", length, grid)),
+        ("loops", loops),
+    ]
+}
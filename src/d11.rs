@@ -1,50 +1,148 @@
 use std::collections::HashSet;
 
+/// How wide every row and column of the image is after the universe expands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expansion {
+    /// Empty rows and columns become this many rows or columns.
+    Uniform(u64),
+    /// Like `Uniform`, but with different factors for rows and columns.
+    PerAxis { rows: u64, cols: u64 },
+    /// The width of every row and column, whether there are galaxies in it or not.
+    Weights { rows: Vec<u64>, cols: Vec<u64> },
+}
+
+impl Expansion {
+    /// The width of every row and column, for an image of the given size.
+    fn weights(&self, board: &Board) -> Result<(Vec<u64>, Vec<u64>), String> {
+        let (rows, cols) = match *self {
+            Expansion::Uniform(factor) => (factor, factor),
+            Expansion::PerAxis { rows, cols } => (rows, cols),
+            Expansion::Weights { ref rows, ref cols } => {
+                if rows.len() < board.height as usize || cols.len() < board.width as usize {
+                    return Err(format!(
+                        "need {} row and {} column weights",
+                        board.height, board.width
+                    ));
+                }
+                return Ok((rows.clone(), cols.clone()));
+            }
+        };
+        let widths = |len: u32, occupied: &HashSet<u32>, factor: u64| {
+            (0..len)
+                .map(|i| if occupied.contains(&i) { 1 } else { factor })
+                .collect()
+        };
+        Ok((
+            widths(board.height, &board.rows_not_expanding, rows),
+            widths(board.width, &board.cols_not_expanding, cols),
+        ))
+    }
+}
+
 struct Board {
     galaxies: Vec<(u32, u32)>,
+    width: u32,
+    height: u32,
     rows_not_expanding: HashSet<u32>,
     cols_not_expanding: HashSet<u32>,
 }
 
 impl Board {
-    fn new(galaxies: Vec<(u32, u32)>) -> Board {
-        let rows_not_expanding = galaxies.iter().map(|&(x, _)| x).collect();
-        let cols_not_expanding = galaxies.iter().map(|&(_, y)| y).collect();
+    fn new(galaxies: Vec<(u32, u32)>, width: u32, height: u32) -> Board {
+        let rows_not_expanding = galaxies.iter().map(|&(_, y)| y).collect();
+        let cols_not_expanding = galaxies.iter().map(|&(x, _)| x).collect();
 
         Board {
             galaxies,
+            width,
+            height,
             rows_not_expanding,
             cols_not_expanding,
         }
     }
+}
+
+/// The galaxies at their positions after the expansion.
+pub struct Universe {
+    /// The positions in the image, as (x, y).
+    galaxies: Vec<(u32, u32)>,
+    /// The positions after the expansion, in the same order.
+    expanded: Vec<(u64, u64)>,
+}
 
-    fn adjusted_distance(&self, i1: usize, i2: usize, expansion: u64) -> u64 {
-        let (x1, y1) = self.galaxies[i1];
-        let (x2, y2) = self.galaxies[i2];
+impl Universe {
+    pub fn new(lines: &[&str], expansion: &Expansion) -> Result<Universe, String> {
+        let width = lines.first().map_or(0, |l| l.len());
+        if lines.iter().any(|l| l.len() != width) {
+            return Err("lines have different lengths".to_string());
+        }
+        let board = parse_board(lines.to_vec());
+        let (rows, cols) = expansion.weights(&board)?;
 
-        let mut dist = 0;
+        // the expanded position of a row or column is the total width of the ones before it
+        let starts = |widths: &[u64]| -> Vec<u64> {
+            widths
+                .iter()
+                .scan(0, |sum, &w| {
+                    let start = *sum;
+                    *sum += w;
+                    Some(start)
+                })
+                .collect()
+        };
+        let (row_starts, col_starts) = (starts(&rows), starts(&cols));
 
-        if x1 != x2 {
-            dist += 1;
+        let expanded = board
+            .galaxies
+            .iter()
+            .map(|&(x, y)| (col_starts[x as usize], row_starts[y as usize]))
+            .collect();
+        Ok(Universe {
+            galaxies: board.galaxies,
+            expanded,
+        })
+    }
 
-            let rows = if x1 < x2 { (x1 + 1)..x2 } else { (x2 + 1)..x1 };
-            for x in rows {
-                let expanding = !self.rows_not_expanding.contains(&x);
-                dist += if expanding { expansion } else { 1 };
-            }
-        }
+    /// The positions of the galaxies in the image, as (x, y), in reading order.
+    pub fn galaxies(&self) -> &[(u32, u32)] {
+        &self.galaxies
+    }
 
-        if y1 != y2 {
-            dist += 1;
+    /// The length of the shortest path between two galaxies, by their index in `galaxies`.
+    pub fn distance(&self, a: usize, b: usize) -> u64 {
+        let ((x1, y1), (x2, y2)) = (self.expanded[a], self.expanded[b]);
+        x1.abs_diff(x2) + y1.abs_diff(y2)
+    }
 
-            let cols = if y1 < y2 { (y1 + 1)..y2 } else { (y2 + 1)..y1 };
-            for y in cols {
-                let expanding = !self.cols_not_expanding.contains(&y);
-                dist += if expanding { expansion } else { 1 };
+    /// The sum of the distances between all pairs of galaxies. Both axes are independent, and
+    /// after sorting the i-th position is subtracted by the i positions before it.
+    pub fn total_distance(&self) -> u64 {
+        let axis = |mut positions: Vec<u64>| -> u64 {
+            positions.sort_unstable();
+            let mut before = 0;
+            let mut total = 0;
+            for (i, &p) in positions.iter().enumerate() {
+                total += p * i as u64 - before;
+                before += p;
             }
-        }
+            total
+        };
+        axis(self.expanded.iter().map(|&(x, _)| x).collect())
+            + axis(self.expanded.iter().map(|&(_, y)| y).collect())
+    }
 
-        dist
+    /// The `k` galaxies closest to galaxy `a` with their distances, closest first.
+    pub fn nearest(&self, a: usize, k: usize) -> Vec<(usize, u64)> {
+        let mut others: Vec<(usize, u64)> = (0..self.galaxies.len())
+            .filter(|&b| b != a)
+            .map(|b| (b, self.distance(a, b)))
+            .collect();
+        if k < others.len() {
+            others.select_nth_unstable_by_key(k, |&(b, d)| (d, b));
+            others.truncate(k);
+        }
+        others.sort_by_key(|&(b, d)| (d, b));
+        others
     }
 }
 
@@ -59,17 +157,45 @@ fn parse_board(lines: Vec<&str>) -> Board {
         }
     }
 
-    Board::new(galaxies)
+    let width = lines.first().map_or(0, |l| l.len()) as u32;
+    Board::new(galaxies, width, lines.len() as u32)
 }
 
-pub fn run(lines: Vec<&str>, expansion: u64) -> u64 {
-    let board = parse_board(lines);
-
-    let mut total = 0;
-    for i1 in 0..board.galaxies.len() {
-        for i2 in (i1 + 1)..board.galaxies.len() {
-            total += board.adjusted_distance(i1, i2, expansion);
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let width = lines.first().ok_or("no lines")?.len();
+    for line in lines {
+        if line.len() != width {
+            return Err("lines have different lengths".to_string());
+        }
+        if let Some(c) = line.chars().find(|&c| c != '.' && c != '#') {
+            return Err(format!("invalid character: {}", c));
         }
     }
-    total
+    if parse_board(lines.to_vec()).galaxies.len() < 2 {
+        return Err("less than two galaxies".to_string());
+    }
+    Ok(())
+}
+
+pub fn run(lines: Vec<&str>, expansion: u64) -> u64 {
+    Universe::new(&lines, &Expansion::Uniform(expansion))
+        .unwrap()
+        .total_distance()
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let board = parse_board(lines.clone());
+
+    let mut xs: Vec<u32> = board.cols_not_expanding.iter().copied().collect();
+    xs.sort();
+    let mut ys: Vec<u32> = board.rows_not_expanding.iter().copied().collect();
+    ys.sort();
+
+    let universe = Universe::new(&lines, &Expansion::Uniform(2)).unwrap();
+
+    vec![
+        ("galaxies", format!("{:?}\n", board.galaxies)),
+        ("occupied", format!("x: {:?}\ny: {:?}\n", xs, ys)),
+        ("expanded", format!("{:?}\n", universe.expanded)),
+    ]
 }
@@ -1,92 +1,301 @@
-use std::collections::HashMap;
-
 use itertools::{repeat_n, Itertools};
 
-struct Matcher {
+use crate::rng::Rng;
+
+/// A row of springs with its group sizes, with the number of arrangements of every suffix.
+#[derive(Debug, Clone)]
+pub struct Row {
     pattern: Vec<char>,
-    cache: HashMap<(usize, u32, usize), u64>,
+    groups: Vec<u32>,
+    /// `ways[i][g]` arrangements of `pattern[i..]` with `groups[g..]`, when `pattern[i]` can
+    /// start a group.
+    ways: Vec<Vec<u64>>,
 }
 
-impl Matcher {
-    fn new(pattern: Vec<char>) -> Self {
-        Self {
+impl Row {
+    pub fn new(pattern: &str, groups: &[u32]) -> Result<Row, String> {
+        if let Some(ch) = pattern.chars().find(|&ch| !"#.?".contains(ch)) {
+            return Err(format!("invalid spring: {}", ch));
+        }
+        let pattern: Vec<char> = pattern.chars().collect();
+        let (n, m) = (pattern.len(), groups.len());
+
+        let mut row = Row {
             pattern,
-            cache: HashMap::new(),
+            groups: groups.to_vec(),
+            ways: vec![vec![0; m + 1]; n + 1],
+        };
+        row.ways[n][m] = 1;
+        for i in (0..n).rev() {
+            for g in 0..=m {
+                let mut total = 0;
+                if row.pattern[i] != '#' {
+                    total += row.ways[i + 1][g];
+                }
+                if let Some(next) = row.group_at(i, g) {
+                    total += row.ways[next][g + 1];
+                }
+                row.ways[i][g] = total;
+            }
         }
+        Ok(row)
     }
 
-    fn count_matches(&mut self, p_idx: usize, c: &[u32]) -> u64 {
-        let mut count = 0;
-        for i in p_idx..self.pattern.len() {
-            let here = self.pattern[i];
-            match here {
-                '?' => {
-                    let cache_key = (i, count, c.len());
+    /// Parses a line of the puzzle input, unfolded `folds` times.
+    pub fn parse(line: &str, folds: usize) -> Result<Row, String> {
+        let (pattern, groups) = parse_line(line, folds)?;
+        Row::new(&pattern.iter().collect::<String>(), &groups)
+    }
 
-                    if let Some(value) = self.cache.get(&cache_key) {
-                        return *value;
-                    }
+    /// Where the next group starts looking, if group `g` can start at `i`.
+    fn group_at(&self, i: usize, g: usize) -> Option<usize> {
+        let len = *self.groups.get(g)? as usize;
+        let end = i + len;
+        let fits = end <= self.pattern.len()
+            && self.pattern[i..end].iter().all(|&c| c != '.')
+            && self.pattern.get(end) != Some(&'#');
+        fits.then(|| (end + 1).min(self.pattern.len()))
+    }
 
-                    let mut total = 0;
-                    if !c.is_empty() {
-                        self.pattern[i] = '#';
-                        total += self.count_matches(p_idx, c);
+    pub fn count(&self) -> u64 {
+        self.ways[0][0]
+    }
+
+    /// The `k`-th arrangement in lexicographic order, where '#' comes before '.'.
+    pub fn nth_arrangement(&self, mut k: u64) -> Option<String> {
+        if k >= self.count() {
+            return None;
+        }
+        let mut res = String::with_capacity(self.pattern.len());
+        let (mut i, mut g) = (0, 0);
+        while i < self.pattern.len() {
+            if let Some(next) = self.group_at(i, g) {
+                let with_group = self.ways[next][g + 1];
+                if k < with_group {
+                    let len = self.groups[g] as usize;
+                    res.extend(repeat_n('#', len));
+                    if next > i + len {
+                        res.push('.');
                     }
-                    self.pattern[i] = '.';
-                    total += self.count_matches(p_idx, c);
-                    self.pattern[i] = '?';
+                    (i, g) = (next, g + 1);
+                    continue;
+                }
+                k -= with_group;
+            }
+            res.push('.');
+            i += 1;
+        }
+        Some(res)
+    }
+
+    /// Every arrangement in lexicographic order, computed when needed.
+    pub fn arrangements(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.count()).map(|k| self.nth_arrangement(k).unwrap())
+    }
+
+    /// An arrangement where every one is equally likely, or `None` if there are none.
+    pub fn sample(&self, rng: &mut Rng) -> Option<String> {
+        let count = self.count();
+        (count > 0).then(|| self.nth_arrangement(rng.below(count)).unwrap())
+    }
 
-                    self.cache.insert(cache_key, total);
+    /// For every spring, in how many arrangements it is damaged.
+    pub fn damaged_counts(&self) -> Vec<u64> {
+        let (n, m) = (self.pattern.len(), self.groups.len());
 
-                    return total;
+        // `before[i][g]` ways to arrange `pattern[..i]` with `groups[..g]`, so the rest can
+        // start at `i`
+        let mut before = vec![vec![0u64; m + 1]; n + 1];
+        before[0][0] = 1;
+        // how many more arrangements have a damaged spring here than at the spring before
+        let mut starts = vec![0u64; n + 1];
+        for i in 0..n {
+            for g in 0..=m {
+                let here = before[i][g];
+                if here == 0 {
+                    continue;
                 }
-                '#' => {
-                    count += 1;
+                if self.pattern[i] != '#' {
+                    before[i + 1][g] += here;
                 }
-                '.' => {
-                    if count != 0 {
-                        if c.is_empty() || count != c[0] {
-                            return 0;
-                        } else {
-                            return self.count_matches(i, &c[1..]);
+                if let Some(next) = self.group_at(i, g) {
+                    before[next][g + 1] += here;
+                    let both = here * self.ways[next][g + 1];
+                    // differences, so they can wrap until the running sum below
+                    starts[i] = starts[i].wrapping_add(both);
+                    let end = i + self.groups[g] as usize;
+                    starts[end] = starts[end].wrapping_sub(both);
+                }
+            }
+        }
+        starts
+            .iter()
+            .take(n)
+            .scan(0, |open, &d| {
+                *open = d.wrapping_add(*open);
+                Some(*open)
+            })
+            .collect()
+    }
+}
+
+/// A picross puzzle, where every row and column of the grid is a row of springs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    rows: Vec<Vec<u32>>,
+    cols: Vec<Vec<u32>>,
+}
+
+/// The solutions of a nonogram, as lines in the puzzle input format with the row groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solutions {
+    None,
+    Unique(Vec<String>),
+    /// Two of the solutions.
+    Many(Vec<String>, Vec<String>),
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<u32>>, cols: Vec<Vec<u32>>) -> Result<Nonogram, String> {
+        if rows.is_empty() || cols.is_empty() {
+            return Err("no rows or columns".to_string());
+        }
+        if rows.iter().chain(cols.iter()).flatten().any(|&g| g == 0) {
+            return Err("empty group".to_string());
+        }
+        Ok(Nonogram { rows, cols })
+    }
+
+    /// Fills in the springs that are the same in every arrangement of their row and column,
+    /// until nothing changes. Returns false if some row or column has no arrangements.
+    fn propagate(&self, grid: &mut [Vec<char>]) -> bool {
+        let (height, width) = (self.rows.len(), self.cols.len());
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (horizontal, count) in [(true, height), (false, width)] {
+                for i in 0..count {
+                    let (cells, groups): (Vec<(usize, usize)>, _) = if horizontal {
+                        ((0..width).map(|x| (x, i)).collect(), &self.rows[i])
+                    } else {
+                        ((0..height).map(|y| (i, y)).collect(), &self.cols[i])
+                    };
+                    let pattern: String = cells.iter().map(|&(x, y)| grid[y][x]).collect();
+                    let row = Row::new(&pattern, groups).unwrap();
+                    let total = row.count();
+                    if total == 0 {
+                        return false;
+                    }
+                    for (&(x, y), damaged) in cells.iter().zip(row.damaged_counts()) {
+                        let known = match damaged {
+                            0 => '.',
+                            d if d == total => '#',
+                            _ => continue,
+                        };
+                        if grid[y][x] == '?' {
+                            grid[y][x] = known;
+                            changed = true;
                         }
                     }
                 }
-                _ => panic!("unknown character: {}", here),
-            };
+            }
         }
+        true
+    }
 
-        let is_match = match c {
-            [] => count == 0,
-            [e] => count == *e,
-            _ => false,
+    /// Adds up to `limit` solutions reachable from the grid to `found`.
+    fn search(&self, mut grid: Vec<Vec<char>>, found: &mut Vec<Vec<Vec<char>>>, limit: usize) {
+        if found.len() >= limit || !self.propagate(&mut grid) {
+            return;
+        }
+        let unknown = grid
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| row.iter().position(|&c| c == '?').map(|x| (x, y)));
+        let Some((x, y)) = unknown else {
+            found.push(grid);
+            return;
         };
+        for guess in ['#', '.'] {
+            let mut next = grid.clone();
+            next[y][x] = guess;
+            self.search(next, found, limit);
+        }
+    }
+
+    pub fn solve(&self) -> Solutions {
+        let grid = vec![vec!['?'; self.cols.len()]; self.rows.len()];
+        let mut found = Vec::new();
+        self.search(grid, &mut found, 2);
 
-        if is_match {
-            1
-        } else {
-            0
+        let mut lines = found.into_iter().map(|grid| {
+            grid.iter()
+                .zip(self.rows.iter())
+                .map(|(row, groups)| {
+                    let groups: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+                    format!("{} {}", row.iter().collect::<String>(), groups.join(","))
+                })
+                .collect()
+        });
+        match (lines.next(), lines.next()) {
+            (None, _) => Solutions::None,
+            (Some(one), None) => Solutions::Unique(one),
+            (Some(one), Some(two)) => Solutions::Many(one, two),
         }
     }
 }
 
-fn parse_line(line: &str, folds: usize) -> (Vec<char>, Vec<u32>) {
-    let (s, c) = line.split_once(' ').unwrap();
+fn parse_line(line: &str, folds: usize) -> Result<(Vec<char>, Vec<u32>), String> {
+    let (s, c) = line.split_once(' ').ok_or("missing group sizes")?;
+    if let Some(ch) = s.chars().find(|&ch| !"#.?".contains(ch)) {
+        return Err(format!("invalid spring: {}", ch));
+    }
     let s = repeat_n(s, folds).join("?");
+    let no_groups = c.is_empty();
     let c = repeat_n(c, folds).join(",");
 
     let pattern = s.chars().collect();
-    let groups: Vec<u32> = c.split(',').map(|s| s.parse::<u32>().unwrap()).collect();
+    let groups: Vec<u32> = if no_groups {
+        Vec::new() // rows without damaged springs have no groups
+    } else {
+        c.split(',')
+            .map(|s| s.parse::<u32>().map_err(|e| format!("invalid group size {}: {}", s, e)))
+            .collect::<Result<_, _>>()?
+    };
+
+    Ok((pattern, groups))
+}
 
-    (pattern, groups)
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no rows".to_string());
+    }
+    for line in lines {
+        parse_line(line, 1)?;
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
     let mut total = 0;
     for line in lines {
-        let (s, c) = parse_line(line, if part1 { 1 } else { 5 });
-        let res = Matcher::new(s).count_matches(0, &c);
-        total += res;
+        total += Row::parse(line, if part1 { 1 } else { 5 }).unwrap().count();
     }
     total
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut text = String::new();
+    let mut damaged = String::new();
+    for line in lines {
+        let (s, c) = parse_line(line, 1).unwrap();
+        let row = Row::parse(line, 1).unwrap();
+        text += &format!("{} {:?} -> {}\n", s.iter().collect::<String>(), c, row.count());
+
+        damaged += &format!("{} {:?}\n", line, row.damaged_counts());
+        for arrangement in row.arrangements().take(4) {
+            damaged += &format!("  {}\n", arrangement);
+        }
+    }
+    vec![("arrangements", text), ("damaged", damaged)]
+}
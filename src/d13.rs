@@ -1,66 +1,120 @@
 use itertools::Itertools;
 
-struct Board {
-    rows: Vec<Vec<bool>>,
+/// Where a pattern is mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Axis {
+    /// Between rows `n - 1` and `n`.
+    Horizontal(usize),
+    /// Between columns `n - 1` and `n`.
+    Vertical(usize),
+    /// From the top left to the bottom right corner, only for square patterns.
+    Diagonal,
+    /// From the top right to the bottom left corner, only for square patterns.
+    AntiDiagonal,
 }
 
-impl Board {
-    fn horizontal_reflections(&self) -> Vec<usize> {
-        let mut refl = Vec::new();
+/// Two cells (x, y) that are each other's mirror image.
+pub type CellPair = ((usize, usize), (usize, usize));
 
-        for y in 0..(self.rows.len() - 1) {
-            for d in 0.. {
-                let lo = y - d;
-                let hi = y + 1 + d;
+/// An axis and the pairs of mirrored cells that differ, one of each pair has a smudge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub smudges: Vec<CellPair>,
+}
 
-                if self.rows[lo] != self.rows[hi] {
-                    break;
-                }
-                if lo == 0 || hi == self.rows.len() - 1 {
-                    refl.push(y + 1);
-                    break;
-                }
-            }
+impl Reflection {
+    /// The number of rows or columns before the axis, times 100 for rows, as in the puzzle.
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal(y) => y * 100,
+            Axis::Vertical(x) => x,
+            Axis::Diagonal | Axis::AntiDiagonal => 0,
         }
-
-        refl
     }
+}
 
-    fn fuzzy_horizontal_reflection(&mut self) -> Option<usize> {
-        let originals = self.horizontal_reflections();
-
-        for y in 0..self.rows.len() {
-            for x in 0..self.rows[0].len() {
-                let value = self.rows[y][x];
-                self.rows[y][x] = !value;
-                let fixed = self.horizontal_reflections();
-                self.rows[y][x] = value;
+pub struct Board {
+    rows: Vec<Vec<bool>>,
+}
 
-                for idx in fixed {
-                    if !originals.contains(&idx) {
-                        return Some(idx);
-                    }
-                }
+impl Board {
+    pub fn parse(lines: &[&str]) -> Result<Board, String> {
+        let width = lines.first().ok_or("empty pattern")?.len();
+        for line in lines {
+            if line.len() != width {
+                return Err("lines have different lengths".to_string());
+            }
+            if let Some(c) = line.chars().find(|&c| c != '.' && c != '#') {
+                return Err(format!("invalid character: {}", c));
             }
         }
-
-        None
+        Ok(parse_board(lines))
     }
 
-    fn transposed(&self) -> Board {
-        let mut cols = Vec::with_capacity(self.rows[0].len());
-
-        for x in 0..self.rows[0].len() {
-            let mut col = Vec::with_capacity(self.rows.len());
+    fn width(&self) -> usize {
+        self.rows[0].len()
+    }
 
-            for y in 0..self.rows.len() {
-                col.push(self.rows[y][x]);
+    /// Collects the pairs of cells that differ, giving up once there are more than `limit`.
+    fn mismatches(
+        &self,
+        pairs: impl Iterator<Item = CellPair>,
+        limit: usize,
+    ) -> Option<Vec<CellPair>> {
+        let mut found = Vec::new();
+        for ((x1, y1), (x2, y2)) in pairs {
+            if self.rows[y1][x1] != self.rows[y2][x2] {
+                if found.len() == limit {
+                    return None;
+                }
+                found.push(((x1, y1), (x2, y2)));
             }
-
-            cols.push(col);
         }
+        Some(found)
+    }
 
-        Board { rows: cols }
+    /// The reflection in `axis` if exactly `smudges` of the mirrored pairs differ.
+    fn reflection(
+        &self,
+        axis: Axis,
+        pairs: impl Iterator<Item = CellPair>,
+        smudges: usize,
+    ) -> Option<Reflection> {
+        let cells = self.mismatches(pairs, smudges)?;
+        (cells.len() == smudges).then_some(Reflection {
+            axis,
+            smudges: cells,
+        })
+    }
+
+    /// Every axis where exactly `smudges` pairs of mirrored cells differ. Diagonal axes are
+    /// only checked if asked for and the pattern is square.
+    pub fn reflections(&self, smudges: usize, diagonals: bool) -> Vec<Reflection> {
+        let (width, height) = (self.width(), self.rows.len());
+        let mut found = Vec::new();
+
+        for y in 1..height {
+            let reach = y.min(height - y);
+            let pairs = (0..reach)
+                .flat_map(|d| (0..width).map(move |x| ((x, y - 1 - d), (x, y + d))));
+            found.extend(self.reflection(Axis::Horizontal(y), pairs, smudges));
+        }
+        for x in 1..width {
+            let reach = x.min(width - x);
+            let pairs = (0..height)
+                .flat_map(|y| (0..reach).map(move |d| ((x - 1 - d, y), (x + d, y))));
+            found.extend(self.reflection(Axis::Vertical(x), pairs, smudges));
+        }
+        if diagonals && width == height {
+            let n = width;
+            let pairs = (0..n).flat_map(|y| (0..y).map(move |x| ((x, y), (y, x))));
+            found.extend(self.reflection(Axis::Diagonal, pairs, smudges));
+            let pairs = (0..n)
+                .flat_map(|y| (0..n - 1 - y).map(move |x| ((x, y), (n - 1 - y, n - 1 - x))));
+            found.extend(self.reflection(Axis::AntiDiagonal, pairs, smudges));
+        }
+        found
     }
 }
 
@@ -72,6 +126,18 @@ fn parse_board(lines: &[&str]) -> Board {
     Board { rows }
 }
 
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let end = lines.iter().rposition(|line| !line.is_empty()).ok_or("no patterns")?;
+    for block in lines[..=end].split(|line| line.is_empty()) {
+        let board = Board::parse(block)?;
+        // every pattern in the puzzle has a mirror, which sets it apart from other grids
+        if board.reflections(0, false).is_empty() {
+            return Err("pattern without a reflection".to_string());
+        }
+    }
+    Ok(())
+}
+
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
     let mut result = 0;
 
@@ -87,25 +153,51 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
             break;
         }
 
-        let mut board = parse_board(&board_rows);
+        let board = parse_board(&board_rows);
+        let smudges = if part1 { 0 } else { 1 };
 
-        if part1 {
-            let h: usize = board.horizontal_reflections().iter().sum();
-            let v: usize = board.transposed().horizontal_reflections().iter().sum();
+        result += board
+            .reflections(smudges, false)
+            .iter()
+            .map(Reflection::summary)
+            .sum::<usize>();
+    }
 
-            result += h * 100 + v;
-        } else {
-            let h = board.fuzzy_horizontal_reflection();
-            let v = board.transposed().fuzzy_horizontal_reflection();
+    result as u64
+}
 
-            if let Some(idx) = h {
-                result += idx * 100;
-            }
-            if let Some(idx) = v {
-                result += idx;
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut text = String::new();
+    let mut smudges = String::new();
+    for (i, block) in lines.split(|line| line.is_empty()).enumerate() {
+        let board = parse_board(block);
+        let axes = |k: usize| -> (Vec<usize>, Vec<usize>) {
+            let found = board.reflections(k, false);
+            let h = found.iter().filter_map(|r| match r.axis {
+                Axis::Horizontal(y) => Some(y),
+                _ => None,
+            });
+            let v = found.iter().filter_map(|r| match r.axis {
+                Axis::Vertical(x) => Some(x),
+                _ => None,
+            });
+            (h.collect(), v.collect())
+        };
+        let ((h, v), (fuzzy_h, fuzzy_v)) = (axes(0), axes(1));
+        text += &format!(
+            "pattern {}: horizontal {:?} vertical {:?} fuzzy horizontal {:?} fuzzy vertical {:?}\n",
+            i,
+            h,
+            v,
+            fuzzy_h.first(),
+            fuzzy_v.first(),
+        );
+
+        for k in 0..=2 {
+            for r in board.reflections(k, true) {
+                smudges += &format!("pattern {} with {} smudges: {:?}\n", i, k, r);
             }
         }
     }
-
-    result as u64
+    vec![("reflections", text), ("smudges", smudges)]
 }
@@ -1,4 +1,9 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
+
+use crate::budget::{Budget, TimedOut};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 enum Tile {
@@ -78,41 +83,78 @@ impl Board {
     }
 }
 
-fn parse_board(lines: &[&str]) -> Board {
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows.iter() {
+            for tile in row.iter() {
+                f.write_char(match tile {
+                    Tile::Empty => '.',
+                    Tile::Round => 'O',
+                    Tile::Cube => '#',
+                })?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_board(lines: &[&str]) -> Result<Board, String> {
     let mut rows = Vec::new();
 
     for line in lines.iter() {
-        rows.push(
-            line.chars()
-                .map(|ch| match ch {
-                    '.' => Tile::Empty,
-                    'O' => Tile::Round,
-                    '#' => Tile::Cube,
-                    _ => panic!("invalid character: {}", ch),
-                })
-                .collect(),
-        );
+        let row: Vec<Tile> = line
+            .chars()
+            .map(|ch| match ch {
+                '.' => Ok(Tile::Empty),
+                'O' => Ok(Tile::Round),
+                '#' => Ok(Tile::Cube),
+                _ => Err(format!("invalid character: {}", ch)),
+            })
+            .collect::<Result<_, _>>()?;
+        if rows.first().is_some_and(|r: &Vec<Tile>| r.len() != row.len()) {
+            return Err("lines have different lengths".to_string());
+        }
+        rows.push(row);
     }
 
-    Board::new(rows)
+    Ok(Board::new(rows))
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if parse_board(lines)?.rows.is_empty() {
+        return Err("no lines".to_string());
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
+    run_with_budget(lines, part1, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the number of tilt cycles performed so far.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    part1: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<u64>> {
     if part1 {
-        let mut board = parse_board(&lines);
+        let mut board = parse_board(&lines).unwrap();
         board.tilt_north();
-        board.total_load()
+        Ok(board.total_load())
     } else {
-        let mut board = parse_board(&lines);
+        let mut board = parse_board(&lines).unwrap();
 
         let mut cache: HashMap<Board, u64> = HashMap::new();
 
         let steps = 1000000000;
         for i in 0..steps {
+            budget.check(|| i)?;
             if let Some(&j) = cache.get(&board) {
                 let cycle_len = i - j;
                 let steps_left = (steps - i) % cycle_len;
-                for _ in 0..steps_left {
+                for k in 0..steps_left {
+                    budget.check(|| i + k)?;
                     board.perform_tilt_cycle();
                 }
                 break;
@@ -122,10 +164,25 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
             board.perform_tilt_cycle();
         }
 
-        board.total_load()
+        Ok(board.total_load())
     }
 }
 
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let board = parse_board(&lines).unwrap();
+
+    let mut tilted = board.clone();
+    tilted.tilt_north();
+
+    let mut cycled = board.clone();
+    cycled.perform_tilt_cycle();
+
+    vec![
+        ("tilted_north", tilted.to_string()),
+        ("one_cycle", cycled.to_string()),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,7 +192,7 @@ mod tests {
         use Tile::*;
 
         assert_eq!(
-            parse_board(&[".O", "#."]),
+            parse_board(&[".O", "#."]).unwrap(),
             Board::new(vec![vec![Empty, Round], vec![Cube, Empty]])
         );
     }
@@ -21,6 +21,29 @@ pub fn run(line: &str, part1: bool) -> u64 {
     }
 }
 
+/// Checks that the input is a single line of `label-` and `label=N` steps.
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let [line] = lines[..] else {
+        return Err("expected exactly one line".to_string());
+    };
+    for step in line.split(',') {
+        let label = if let Some(label) = step.strip_suffix('-') {
+            label
+        } else if let Some((label, focus)) = step.split_once('=') {
+            focus
+                .parse::<u64>()
+                .map_err(|e| format!("invalid focal length {}: {}", focus, e))?;
+            label
+        } else {
+            return Err(format!("invalid step: {}", step));
+        };
+        if label.is_empty() || !label.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(format!("invalid label: {}", label));
+        }
+    }
+    Ok(())
+}
+
 fn calc_hash(s: &str) -> u8 {
     let mut value: u8 = 0;
     for ch in s.chars() {
@@ -75,3 +98,36 @@ impl<'a> HashMap<'a> {
         power
     }
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let steps: Vec<&str> = lines[0].split(',').collect();
+
+    let hashes = steps
+        .iter()
+        .map(|s| format!("{} -> {}\n", s, calc_hash(s)))
+        .collect();
+
+    let mut hashmap = HashMap::new();
+    for &step in steps.iter() {
+        if let Some(label) = step.strip_suffix('-') {
+            hashmap.remove(label);
+        } else if let Some((label, focus)) = step.split_once('=') {
+            hashmap.add(Lens {
+                label,
+                focal_length: focus.parse().unwrap(),
+            });
+        }
+    }
+    let mut boxes = String::new();
+    for (box_idx, b) in hashmap.boxes.iter().enumerate() {
+        if !b.is_empty() {
+            boxes += &format!("Box {}:", box_idx);
+            for l in b {
+                boxes += &format!(" [{} {}]", l.label, l.focal_length);
+            }
+            boxes += "\n";
+        }
+    }
+
+    vec![("hashes", hashes), ("boxes", boxes)]
+}
@@ -1,8 +1,9 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
 use queues::{IsQueue, Queue};
 
+use crate::budget::{Budget, TimedOut};
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 enum Direction {
     Up,
@@ -57,6 +58,9 @@ impl Tile {
     }
 }
 
+/// How many states the search visits between looking at the clock.
+const STATES_PER_BUDGET_CHECK: usize = 4096;
+
 struct Grid {
     rows: Vec<Vec<Tile>>,
 }
@@ -76,18 +80,35 @@ impl Grid {
         }
     }
 
-    fn simulate_beam(&self, start: (usize, usize, Direction)) -> u32 {
+    fn simulate_beam(
+        &self,
+        start: (usize, usize, Direction),
+        budget: &Budget,
+    ) -> Result<u32, TimedOut<u32>> {
+        Ok(self.energized_tiles(start, budget)?.len() as u32)
+    }
+
+    fn energized_tiles(
+        &self,
+        start: (usize, usize, Direction),
+        budget: &Budget,
+    ) -> Result<HashSet<(usize, usize)>, TimedOut<u32>> {
         let mut q: Queue<(usize, usize, Direction)> = Queue::new();
         q.add(start).unwrap();
 
         let mut visited: HashSet<(usize, usize, Direction)> = HashSet::new();
+        let mut energized: HashSet<(usize, usize)> = HashSet::new();
 
         while let Ok(state) = q.remove() {
             if !visited.insert(state) {
                 continue;
             }
-
             let (x, y, dir) = state;
+            energized.insert((x, y));
+            if visited.len().is_multiple_of(STATES_PER_BUDGET_CHECK) {
+                budget.check(|| energized.len() as u32)?;
+            }
+
             let tile = self.tile_at(x, y);
             match tile.apply(dir) {
                 TileEffect::Just(d) => {
@@ -105,63 +126,91 @@ impl Grid {
             }
         }
 
-        visited.iter().map(|(x, y, _)| (x, y)).unique().count() as u32
+        Ok(energized)
     }
 }
 
-fn parse_grid(lines: Vec<&str>) -> Grid {
+fn parse_grid(lines: Vec<&str>) -> Result<Grid, String> {
     let mut rows = Vec::new();
 
     for line in lines {
-        rows.push(
-            line.chars()
-                .map(|ch| match ch {
-                    '.' => Tile::Empty,
-                    '\\' => Tile::BackMirror,
-                    '/' => Tile::ForwardMirror,
-                    '|' => Tile::VerticalSplitter,
-                    '-' => Tile::HorizontalSplitter,
-                    _ => panic!("invalid tile: {}", ch),
-                })
-                .collect(),
-        );
+        let row: Vec<Tile> = line
+            .chars()
+            .map(|ch| match ch {
+                '.' => Ok(Tile::Empty),
+                '\\' => Ok(Tile::BackMirror),
+                '/' => Ok(Tile::ForwardMirror),
+                '|' => Ok(Tile::VerticalSplitter),
+                '-' => Ok(Tile::HorizontalSplitter),
+                _ => Err(format!("invalid tile: {}", ch)),
+            })
+            .collect::<Result<_, _>>()?;
+        if rows.first().is_some_and(|r: &Vec<Tile>| r.len() != row.len()) {
+            return Err("lines have different lengths".to_string());
+        }
+        rows.push(row);
     }
 
-    Grid { rows }
+    Ok(Grid { rows })
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if parse_grid(lines.to_vec())?.rows.is_empty() {
+        return Err("no lines".to_string());
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
-    let grid = parse_grid(lines);
+    run_with_budget(lines, part1, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the number of tiles energized so far in part 1,
+/// and the best result of the starts tried so far in part 2.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    part1: bool,
+    budget: &Budget,
+) -> Result<u32, TimedOut<u32>> {
+    let grid = parse_grid(lines).unwrap();
     if part1 {
-        grid.simulate_beam((0, 0, Direction::Right))
+        grid.simulate_beam((0, 0, Direction::Right), budget)
     } else {
         let w = grid.rows[0].len();
         let h = grid.rows.len();
 
         let mut best = 0;
 
-        for x in 0..w {
-            let res = grid.simulate_beam((x, 0, Direction::Down));
-            if res > best {
-                best = res;
-            }
-            let res = grid.simulate_beam((x, h - 1, Direction::Up));
-            if res > best {
-                best = res;
-            }
-        }
+        let starts = (0..w)
+            .flat_map(|x| [(x, 0, Direction::Down), (x, h - 1, Direction::Up)])
+            .chain((0..h).flat_map(|y| [(0, y, Direction::Right), (w - 1, y, Direction::Left)]));
 
-        for y in 0..h {
-            let res = grid.simulate_beam((0, y, Direction::Right));
-            if res > best {
-                best = res;
-            }
-            let res = grid.simulate_beam((w - 1, y, Direction::Left));
+        for start in starts {
+            budget.check(|| best)?;
+            let res = grid
+                .simulate_beam(start, budget)
+                .map_err(|_| TimedOut { partial: best })?;
             if res > best {
                 best = res;
             }
         }
 
-        best
+        Ok(best)
+    }
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let grid = parse_grid(lines).unwrap();
+    let energized = grid
+        .energized_tiles((0, 0, Direction::Right), &Budget::unlimited())
+        .unwrap();
+
+    let mut text = String::new();
+    for y in 0..grid.rows.len() {
+        for x in 0..grid.rows[0].len() {
+            text.push(if energized.contains(&(x, y)) { '#' } else { '.' });
+        }
+        text.push('\n');
     }
+    vec![("energized", text)]
 }
@@ -5,6 +5,8 @@ use std::{
 
 use priority_queue::PriorityQueue;
 
+use crate::budget::{Budget, TimedOut};
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 enum Direction {
     Up,
@@ -79,12 +81,20 @@ impl State {
     }
 }
 
+/// How many states the search visits between looking at the clock.
+const STATES_PER_BUDGET_CHECK: usize = 4096;
+
 struct Heatmap {
     rows: Vec<Vec<u8>>,
 }
 
 impl Heatmap {
-    fn find_best_path(&self, min_forward: u8, max_forward: u8) -> u32 {
+    fn find_best_path(
+        &self,
+        min_forward: u8,
+        max_forward: u8,
+        budget: &Budget,
+    ) -> Result<u32, TimedOut<u32>> {
         let width = self.rows[0].len();
         let height = self.rows.len();
 
@@ -98,6 +108,9 @@ impl Heatmap {
             if !seen.insert(state.clone()) {
                 continue;
             }
+            if seen.len().is_multiple_of(STATES_PER_BUDGET_CHECK) {
+                budget.check(|| heat)?;
+            }
 
             let State {
                 x,
@@ -110,7 +123,7 @@ impl Heatmap {
                 if max_forward - forwards_left < min_forward {
                     continue;
                 }
-                return heat;
+                return Ok(heat);
             }
 
             for (dir, reset_f) in [
@@ -138,21 +151,58 @@ impl Heatmap {
     }
 }
 
-fn parse_heatmap(lines: Vec<&str>) -> Heatmap {
+fn parse_heatmap(lines: Vec<&str>) -> Result<Heatmap, String> {
     let mut rows = Vec::new();
 
     for line in lines {
-        rows.push(line.chars().map(|ch| ch as u8 - b'0').collect());
+        let row: Vec<u8> = line
+            .chars()
+            .map(|ch| match ch {
+                '1'..='9' => Ok(ch as u8 - b'0'),
+                _ => Err(format!("invalid heat loss: {}", ch)),
+            })
+            .collect::<Result<_, _>>()?;
+        if rows.first().is_some_and(|r: &Vec<u8>| r.len() != row.len()) {
+            return Err("lines have different lengths".to_string());
+        }
+        rows.push(row);
     }
 
-    Heatmap { rows }
+    Ok(Heatmap { rows })
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if parse_heatmap(lines.to_vec())?.rows.is_empty() {
+        return Err("no lines".to_string());
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
-    let heatmap = parse_heatmap(lines);
+    run_with_budget(lines, part1, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is a lower bound for the heat loss,
+/// since the search explores paths in order of increasing heat loss.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    part1: bool,
+    budget: &Budget,
+) -> Result<u32, TimedOut<u32>> {
+    let heatmap = parse_heatmap(lines).unwrap();
     if part1 {
-        heatmap.find_best_path(1, 3)
+        heatmap.find_best_path(1, 3, budget)
     } else {
-        heatmap.find_best_path(4, 10)
+        heatmap.find_best_path(4, 10, budget)
+    }
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let heatmap = parse_heatmap(lines).unwrap();
+    let mut text = format!("{}x{}\n", heatmap.rows[0].len(), heatmap.rows.len());
+    for (min, max) in [(1, 3), (4, 10)] {
+        let heat = heatmap.find_best_path(min, max, &Budget::unlimited()).unwrap();
+        text += &format!("forward {}..={}: {}\n", min, max, heat);
     }
+    vec![("best_paths", text)]
 }
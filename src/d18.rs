@@ -64,6 +64,10 @@ impl PlanStep {
                 .strip_suffix(')')
                 .ok_or_else(|| "missing ')' after color".to_string())?;
 
+            if color.len() != 6 || !color.is_ascii() {
+                return Err(format!("expected 6 hex digits: {}", color));
+            }
+
             let dir = color.chars().nth(5).unwrap();
             let dir = match dir {
                 '0' => Dir::Right,
@@ -242,6 +246,17 @@ fn execute_plan(plan: Vec<PlanStep>) -> u64 {
     total
 }
 
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no steps".to_string());
+    }
+    for line in lines {
+        PlanStep::from_line(line, false)?;
+        PlanStep::from_line(line, true)?;
+    }
+    Ok(())
+}
+
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
     let mut plan = Vec::new();
 
@@ -251,3 +266,25 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
 
     execute_plan(plan)
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let plan: Vec<PlanStep> = lines
+        .iter()
+        .map(|line| PlanStep::from_line(line, false).unwrap())
+        .collect();
+
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let mut here = (0, 0);
+    for s in plan.iter() {
+        here = s.dir.forward(here, s.steps as isize);
+        xs.push(here.0 as i32);
+        ys.push(here.1 as i32);
+    }
+
+    vec![
+        ("plan", plan.iter().map(|s| format!("{:?}\n", s)).collect()),
+        ("grid_x", format!("{:?}\n", gridify(xs))),
+        ("grid_y", format!("{:?}\n", gridify(ys))),
+    ]
+}
@@ -27,13 +27,24 @@ impl Condition {
     }
 }
 
-fn parse_condition(s: &str) -> Condition {
+fn parse_category(name: &str) -> Result<u8, String> {
+    match name {
+        "x" | "m" | "a" | "s" => Ok(name.as_bytes()[0]),
+        _ => Err(format!("unknown category: {}", name)),
+    }
+}
+
+fn parse_condition(s: &str) -> Result<Condition, String> {
+    let parse_num = |num: &str| {
+        num.parse::<u32>()
+            .map_err(|e| format!("invalid number {}: {}", num, e))
+    };
     if let Some((name, num)) = s.split_once('<') {
-        Condition::LessThan(name.as_bytes()[0], num.parse().unwrap())
+        Ok(Condition::LessThan(parse_category(name)?, parse_num(num)?))
     } else if let Some((name, num)) = s.split_once('>') {
-        Condition::GreaterThan(name.as_bytes()[0], num.parse().unwrap())
+        Ok(Condition::GreaterThan(parse_category(name)?, parse_num(num)?))
     } else {
-        panic!("unknown condition: {}", s);
+        Err(format!("unknown condition: {}", s))
     }
 }
 
@@ -68,16 +79,16 @@ impl<'a> Rule<'a> {
     }
 }
 
-fn parse_rule<'a>(s: &'a str) -> Rule<'a> {
+fn parse_rule<'a>(s: &'a str) -> Result<Rule<'a>, String> {
     match s.split_once(':') {
-        Some((left, right)) => Rule {
-            condition: Some(parse_condition(left)),
+        Some((left, right)) => Ok(Rule {
+            condition: Some(parse_condition(left)?),
             destination: parse_destination(right),
-        },
-        None => Rule {
+        }),
+        None => Ok(Rule {
             condition: None,
             destination: parse_destination(s),
-        },
+        }),
     }
 }
 
@@ -97,11 +108,17 @@ impl<'a> Workflow<'a> {
     }
 }
 
-fn parse_workflow<'a>(line: &'a str) -> Workflow<'a> {
-    let (name, rest) = line.split_once('{').unwrap();
-    let rest = rest.strip_suffix('}').unwrap();
-    let rules = rest.split(',').map(parse_rule).collect();
-    Workflow { name, rules }
+fn parse_workflow<'a>(line: &'a str) -> Result<Workflow<'a>, String> {
+    let (name, rest) = line.split_once('{').ok_or("missing '{'")?;
+    let rest = rest.strip_suffix('}').ok_or("missing '}'")?;
+    if name.is_empty() {
+        return Err("missing workflow name".to_string());
+    }
+    let rules: Vec<Rule> = rest.split(',').map(parse_rule).collect::<Result<_, _>>()?;
+    if rules.last().is_some_and(|r| r.condition.is_some()) {
+        return Err(format!("the last rule of {} has a condition", name));
+    }
+    Ok(Workflow { name, rules })
 }
 
 struct Part {
@@ -245,8 +262,12 @@ impl<'a> SortingSystem<'a> {
     }
 }
 
-fn parse_part(line: &str) -> Part {
-    let line = line.strip_prefix('{').unwrap().strip_suffix('}').unwrap();
+fn parse_part(line: &str) -> Result<Part, String> {
+    let line = line
+        .strip_prefix('{')
+        .ok_or("missing '{'")?
+        .strip_suffix('}')
+        .ok_or("missing '}'")?;
     let mut part = Part {
         x: 0,
         m: 0,
@@ -254,35 +275,60 @@ fn parse_part(line: &str) -> Part {
         s: 0,
     };
     for s in line.split(',') {
-        let (name, value) = s.split_once('=').unwrap();
-        let value = value.parse().unwrap();
+        let (name, value) = s.split_once('=').ok_or("missing '='")?;
+        let value = value
+            .parse()
+            .map_err(|e| format!("invalid rating {}: {}", value, e))?;
         match name {
             "x" => part.x = value,
             "m" => part.m = value,
             "a" => part.a = value,
             "s" => part.s = value,
-            _ => panic!("unknown category: {}", name),
+            _ => return Err(format!("unknown category: {}", name)),
         };
     }
-    part
+    Ok(part)
 }
 
-pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
+fn parse_input<'a>(lines: &[&'a str]) -> Result<(Vec<Workflow<'a>>, Vec<Part>), String> {
     let mut workflows = Vec::new();
     let mut parts = Vec::new();
     let mut workflows_done = false;
 
-    for line in lines {
+    for &line in lines {
         if !workflows_done {
             if line.is_empty() {
                 workflows_done = true;
             } else {
-                workflows.push(parse_workflow(line));
+                workflows.push(parse_workflow(line)?);
             }
         } else {
-            parts.push(parse_part(line));
+            parts.push(parse_part(line)?);
+        }
+    }
+
+    Ok((workflows, parts))
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let (workflows, _) = parse_input(lines)?;
+    if !workflows.iter().any(|w| w.name == "in") {
+        return Err("missing workflow 'in'".to_string());
+    }
+    for w in workflows.iter() {
+        for r in w.rules.iter() {
+            if let Destination::Workflow(name) = r.destination
+                && !workflows.iter().any(|w| w.name == name)
+            {
+                return Err(format!("unknown workflow: {}", name));
+            }
         }
     }
+    Ok(())
+}
+
+pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
+    let (workflows, parts) = parse_input(&lines).unwrap();
 
     let workflows = workflows
         .into_iter()
@@ -295,7 +341,6 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
         for p in parts {
             let mut w = workflows.get("in").unwrap();
             loop {
-                println!("{}", w.name);
                 match w.evaluate(&p) {
                     Destination::Accepted => {
                         result += (p.x + p.m + p.a + p.s) as u64;
@@ -326,3 +371,28 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
 
     result
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let (workflows, _) = parse_input(&lines).unwrap();
+    let workflows = workflows
+        .into_iter()
+        .map(|w| (w.name, w))
+        .collect::<HashMap<_, _>>();
+
+    let sorting_system = SortingSystem { workflows };
+    let accepted = sorting_system.filter(
+        "in",
+        PartRanges {
+            x: 1..4001,
+            m: 1..4001,
+            a: 1..4001,
+            s: 1..4001,
+        },
+    );
+
+    let text = accepted
+        .iter()
+        .map(|r| format!("x={:?} m={:?} a={:?} s={:?}\n", r.x, r.m, r.a, r.s))
+        .collect();
+    vec![("accepted_ranges", text)]
+}
@@ -1,102 +1,215 @@
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct CubeSet {
-    red: u32,
-    green: u32,
-    blue: u32,
+use std::collections::BTreeMap;
+
+/// Number of cubes per colour, colours that are missing count as zero.
+#[derive(Debug, Clone, Default)]
+pub struct CubeSet {
+    counts: BTreeMap<String, u32>,
 }
 
 impl CubeSet {
-    fn new(red: u32, green: u32, blue: u32) -> Self {
-        Self { red, green, blue }
+    pub fn new(counts: &[(&str, u32)]) -> Self {
+        let mut set = Self::default();
+        for &(color, count) in counts {
+            set.counts.insert(color.to_string(), count);
+        }
+        set
+    }
+
+    pub fn get(&self, color: &str) -> u32 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.counts.iter().all(|(color, &count)| count <= bag.get(color))
+    }
+
+    /// The smallest set that contains both sets.
+    pub fn union(&self, other: &CubeSet) -> CubeSet {
+        let mut res = self.clone();
+        for (color, &count) in other.counts.iter() {
+            let c = res.counts.entry(color.clone()).or_insert(0);
+            *c = (*c).max(count);
+        }
+        res
+    }
+
+    /// The product of the counts of the given colours.
+    pub fn power(&self, colors: &[String]) -> u32 {
+        colors.iter().map(|c| self.get(c)).product()
+    }
+
+    fn non_zero(&self) -> impl Iterator<Item = (&String, &u32)> {
+        self.counts.iter().filter(|&(_, &count)| count > 0)
+    }
+}
+
+/// Ignores zero counts, like `get` does.
+impl PartialEq for CubeSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.non_zero().eq(other.non_zero())
     }
 }
 
+impl Eq for CubeSet {}
+
 #[derive(Debug, PartialEq)]
-struct Game {
-    id: u32,
-    sets: Vec<CubeSet>,
+pub struct Game {
+    pub id: u32,
+    pub sets: Vec<CubeSet>,
 }
 
 impl Game {
-    fn possible(&self, max: CubeSet) -> bool {
-        for set in self.sets.iter() {
-            if set.red > max.red || set.green > max.green || set.blue > max.blue {
-                return false;
-            }
-        }
-        true
+    pub fn possible(&self, bag: &CubeSet) -> bool {
+        self.sets.iter().all(|set| set.fits_in(bag))
+    }
+
+    /// The smallest bag that this game could have been played with.
+    pub fn min_bag(&self) -> CubeSet {
+        self.sets
+            .iter()
+            .fold(CubeSet::default(), |bag, set| bag.union(set))
+    }
+
+    /// Indices of the draws that need more cubes than the bag has.
+    pub fn violations(&self, bag: &CubeSet) -> Vec<usize> {
+        self.sets
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| !set.fits_in(bag))
+            .map(|(i, _)| i)
+            .collect()
     }
 }
 
-fn parse_game(line: &str) -> Game {
-    let (left, right) = line.split_once(':').unwrap();
-    let id_str = left.split_once(' ').unwrap().1;
-    let id = id_str.parse::<u32>().unwrap();
+fn parse_game(line: &str) -> Result<Game, String> {
+    let (left, right) = line.split_once(':').ok_or("missing ':'")?;
+    let id_str = left
+        .strip_prefix("Game ")
+        .ok_or_else(|| format!("expected 'Game <id>': {}", left))?;
+    let id = id_str
+        .parse::<u32>()
+        .map_err(|e| format!("invalid game id: {}", e))?;
 
     let mut sets = Vec::new();
     for set in right.split(';') {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
+        let mut cubes = CubeSet::default();
         for part in set.split(',') {
-            let (c, t) = part.trim().split_once(' ').unwrap();
-            let v = c.trim().parse::<u32>().unwrap();
-            match t.trim() {
-                "red" => red = v,
-                "green" => green = v,
-                "blue" => blue = v,
-                _ => panic!("what?"),
+            let (c, t) = part
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| format!("expected '<count> <color>': {}", part))?;
+            let v = c
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("invalid count: {}", e))?;
+            let color = t.trim();
+            if color.is_empty() || !color.chars().all(|c| c.is_alphabetic()) {
+                return Err(format!("invalid color: {}", t));
             }
+            cubes.counts.insert(color.to_string(), v);
         }
-        sets.push(CubeSet::new(red, green, blue));
+        sets.push(cubes);
     }
-    Game { id, sets }
+    Ok(Game { id, sets })
+}
+
+/// All games of the input, together with every colour that appears in them.
+#[derive(Debug)]
+pub struct CubeGames {
+    pub colors: Vec<String>,
+    pub games: Vec<Game>,
+}
+
+impl CubeGames {
+    pub fn parse(lines: &[&str]) -> Result<Self, String> {
+        let games: Vec<Game> = lines.iter().map(|line| parse_game(line)).collect::<Result<_, _>>()?;
+        let mut colors: Vec<String> = games
+            .iter()
+            .flat_map(|g| g.sets.iter().flat_map(|s| s.counts.keys().cloned()))
+            .collect();
+        colors.sort();
+        colors.dedup();
+        Ok(Self { colors, games })
+    }
+
+    /// The smallest bag with which all the chosen games are possible.
+    pub fn min_bag_for(&self, ids: &[u32]) -> Result<CubeSet, String> {
+        let mut bag = CubeSet::default();
+        for &id in ids {
+            let game = self
+                .games
+                .iter()
+                .find(|g| g.id == id)
+                .ok_or_else(|| format!("unknown game: {}", id))?;
+            bag = bag.union(&game.min_bag());
+        }
+        Ok(bag)
+    }
+
+    /// The ids of the games that are not possible with the bag, and the draws that break it.
+    pub fn violations(&self, bag: &CubeSet) -> Vec<(u32, Vec<usize>)> {
+        self.games
+            .iter()
+            .map(|g| (g.id, g.violations(bag)))
+            .filter(|(_, v)| !v.is_empty())
+            .collect()
+    }
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no games".to_string());
+    }
+    CubeGames::parse(lines)?;
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
+    let cube_games = CubeGames::parse(&lines).unwrap();
+
     if part1 {
-        lines
-            .into_iter()
-            .map(parse_game)
-            .filter(|game| game.possible(CubeSet::new(12, 13, 14)))
+        let bag = CubeSet::new(&[("red", 12), ("green", 13), ("blue", 14)]);
+        cube_games
+            .games
+            .iter()
+            .filter(|game| game.possible(&bag))
             .map(|game| game.id)
             .sum()
     } else {
-        lines
-            .into_iter()
-            .map(parse_game)
-            .map(|game| {
-                let mut req = CubeSet::new(0, 0, 0);
-                for set in game.sets.iter() {
-                    if set.red > req.red {
-                        req.red = set.red;
-                    }
-                    if set.green > req.green {
-                        req.green = set.green;
-                    }
-                    if set.blue > req.blue {
-                        req.blue = set.blue;
-                    }
-                }
-                req.red * req.green * req.blue
-            })
+        cube_games
+            .games
+            .iter()
+            .map(|game| game.min_bag().power(&cube_games.colors))
             .sum()
     }
 }
 
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let cube_games = CubeGames::parse(&lines).unwrap();
+    let min_bags = cube_games
+        .games
+        .iter()
+        .map(|g| format!("{}: {:?}\n", g.id, g.min_bag().counts))
+        .collect();
+    vec![
+        ("games", format!("{:#?}\n", cube_games.games)),
+        ("min_bags", min_bags),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn parse_game_works() {
-        let game = parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+        let game = parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
         let expected = Game {
             id: 1,
             sets: vec![
-                CubeSet::new(4, 0, 3),
-                CubeSet::new(1, 2, 6),
-                CubeSet::new(0, 2, 0),
+                CubeSet::new(&[("blue", 3), ("red", 4)]),
+                CubeSet::new(&[("red", 1), ("green", 2), ("blue", 6)]),
+                CubeSet::new(&[("green", 2)]),
             ],
         };
         assert_eq!(game, expected);
@@ -5,6 +5,8 @@ use std::{
     fmt::Display,
 };
 
+use crate::budget::{Budget, TimedOut};
+
 #[derive(Debug, PartialEq, Eq)]
 enum ModuleState<'a> {
     FlipFlop(bool),
@@ -62,8 +64,8 @@ impl<'a> From<(ModuleType, &'a str, Vec<&'a str>)> for Module<'a> {
     }
 }
 
-fn parse_line<'a>(line: &'a str) -> Module<'a> {
-    let (l, r) = line.split_once(" -> ").unwrap();
+fn parse_line<'a>(line: &'a str) -> Result<Module<'a>, String> {
+    let (l, r) = line.split_once(" -> ").ok_or("missing ' -> '")?;
 
     let (name, module_type) = if let Some(name) = l.strip_prefix('%') {
         (name, ModuleType::FlipFlop)
@@ -72,14 +74,14 @@ fn parse_line<'a>(line: &'a str) -> Module<'a> {
     } else if l == "broadcaster" {
         ("broadcaster", ModuleType::Broadcast)
     } else {
-        panic!("unknown module type: {}", l);
+        return Err(format!("unknown module type: {}", l));
     };
 
-    Module {
+    Ok(Module {
         t: module_type,
         name,
         dests: r.split(", ").collect(),
-    }
+    })
 }
 
 fn find_repeating_pattern(numbers: &[((u64, u64), Signal)]) -> Option<(u64, u64)> {
@@ -106,8 +108,20 @@ fn find_repeating_pattern(numbers: &[((u64, u64), Signal)]) -> Option<(u64, u64)
     None
 }
 
-fn parse_input<'a>(lines: Vec<&'a str>) -> ModuleConfig<'a> {
-    ModuleConfig::from_lines(lines.into_iter().map(parse_line).collect())
+fn parse_input<'a>(lines: Vec<&'a str>) -> Result<ModuleConfig<'a>, String> {
+    let modules = lines
+        .into_iter()
+        .map(parse_line)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ModuleConfig::from_lines(modules))
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let config = parse_input(lines.to_vec())?;
+    if !config.states.contains_key("broadcaster") {
+        return Err("missing broadcaster".to_string());
+    }
+    Ok(())
 }
 
 impl<'a> ModuleConfig<'a> {
@@ -157,7 +171,7 @@ impl<'a> ModuleConfig<'a> {
         }
     }
 
-    fn find_first_low(&mut self, target_name: &str) -> u64 {
+    fn find_first_low(&mut self, target_name: &str, budget: &Budget) -> Result<u64, TimedOut<u64>> {
         if target_name == "rx" {
             let mut q: Queue<(&str, Signal, &str)> = Queue::new();
 
@@ -172,6 +186,7 @@ impl<'a> ModuleConfig<'a> {
 
                 let limit = 100000;
                 for _ in 0..limit {
+                    budget.check(|| presses)?;
                     //q.add(("button", Signal::Low, "broadcaster")).unwrap();
                     q.add(("broadcaster", Signal::Low, group_input)).unwrap();
                     presses += 1;
@@ -305,7 +320,7 @@ impl<'a> ModuleConfig<'a> {
                 }
             }
 
-            first_solution.0
+            Ok(first_solution.0)
         } else {
             let mut q: Queue<(&str, Signal, &str)> = Queue::new();
 
@@ -313,12 +328,13 @@ impl<'a> ModuleConfig<'a> {
 
             let limit = 100000;
             for _ in 0..limit {
+                budget.check(|| presses)?;
                 q.add(("button", Signal::Low, "broadcaster")).unwrap();
                 presses += 1;
 
                 while let Ok((from, signal, to)) = q.remove() {
                     if to == target_name && signal == Signal::Low {
-                        return presses;
+                        return Ok(presses);
                     }
 
                     if let Some(state) = self.states.get_mut(to) {
@@ -405,7 +421,7 @@ struct Pattern {
     high_length: u64, // in ticks
 }
 
-fn _make_state_str(modules: &ModuleConfig, compact: bool) -> String {
+fn make_state_str(modules: &ModuleConfig, compact: bool) -> String {
     modules
         .states
         .iter()
@@ -415,22 +431,49 @@ fn _make_state_str(modules: &ModuleConfig, compact: bool) -> String {
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
-    let mut modules = parse_input(lines);
+    run_with_budget(lines, part1, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the number of button presses simulated so far.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    part1: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<u64>> {
+    let mut modules = parse_input(lines).unwrap();
 
     if part1 {
         let mut total_low = 0;
         let mut total_high = 0;
 
-        for _ in 0..1000 {
+        for presses in 0..1000 {
+            budget.check(|| presses)?;
             let (low, high) = modules.simulate_button_press();
             total_low += low;
             total_high += high;
         }
 
-        total_low * total_high
+        Ok(total_low * total_high)
     } else {
-        modules.find_first_low("rx")
+        modules.find_first_low("rx", budget)
+    }
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut modules = parse_input(lines).unwrap();
+
+    let mut presses = format!("start: {}\n", make_state_str(&modules, false));
+    for i in 1..=4 {
+        let (low, high) = modules.simulate_button_press();
+        presses += &format!(
+            "press {}: {} (low {}, high {})\n",
+            i,
+            make_state_str(&modules, false),
+            low,
+            high
+        );
     }
+    vec![("presses", presses)]
 }
 
 #[cfg(test)]
@@ -448,7 +491,8 @@ mod tests {
                 "&b -> c",
                 "%c -> d",
                 "%d -> output",
-            ]),
+            ])
+            .unwrap(),
             ModuleConfig::from_lines(vec![
                 (Broadcast, "broadcaster", vec!["a"]),
                 (FlipFlop, "a", vec!["b"]),
@@ -471,6 +515,6 @@ mod tests {
             (FlipFlop, "d", vec!["output"]),
         ]);
 
-        assert_eq!(modules.find_first_low("output"), 7);
+        assert_eq!(modules.find_first_low("output", &Budget::unlimited()), Ok(7));
     }
 }
@@ -2,6 +2,8 @@ use std::collections::{HashSet, VecDeque};
 
 use num::Integer;
 
+use crate::budget::{Budget, TimedOut};
+
 struct Map {
     width: usize,
     height: usize,
@@ -42,7 +44,41 @@ impl Map {
     }
 }
 
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let width = lines.first().ok_or("no lines")?.len();
+    let mut starts = 0;
+    for line in lines {
+        if line.len() != width {
+            return Err("lines have different lengths".to_string());
+        }
+        for c in line.chars() {
+            match c {
+                '.' | '#' => {}
+                'S' => starts += 1,
+                _ => return Err(format!("invalid character: {}", c)),
+            }
+        }
+    }
+    if starts != 1 {
+        return Err(format!("expected one start position, found {}", starts));
+    }
+    Ok(())
+}
+
 pub fn run(lines: Vec<&str>, steps: u32, repeat: bool) -> u64 {
+    run_with_budget(lines, steps, repeat, &Budget::unlimited()).unwrap()
+}
+
+/// How many plots the search visits between looking at the clock.
+const PLOTS_PER_BUDGET_CHECK: usize = 4096;
+
+/// On time out the partial result is the number of reachable plots found so far.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    steps: u32,
+    repeat: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<u64>> {
     let (map, (sx, sy)) = Map::from_lines(lines);
 
     let shell_size = (map.width.lcm(&map.height) * 2) as u32;
@@ -62,6 +98,9 @@ pub fn run(lines: Vec<&str>, steps: u32, repeat: bool) -> u64 {
             continue;
         }
         visited.insert((x, y));
+        if visited.len().is_multiple_of(PLOTS_PER_BUDGET_CHECK) {
+            budget.check(|| count)?;
+        }
 
         if dist > next_shell_dist {
             count_per_shell.push(count);
@@ -132,5 +171,15 @@ pub fn run(lines: Vec<&str>, steps: u32, repeat: bool) -> u64 {
         count += extra * shells as u64;
     }
 
-    count
+    Ok(count)
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut text = String::new();
+    for steps in 1..=10 {
+        let bounded = run(lines.clone(), steps, false);
+        let repeated = run(lines.clone(), steps, true);
+        text += &format!("{} steps: {} bounded, {} repeated\n", steps, bounded, repeated);
+    }
+    vec![("reachable", text)]
 }
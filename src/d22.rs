@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::budget::{Budget, TimedOut};
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 struct Point {
     x: i64,
@@ -8,14 +10,17 @@ struct Point {
 }
 
 impl Point {
-    fn parse_str(s: &str) -> Self {
-        let nums: Vec<i64> = s.split(',').map(|s| s.parse::<i64>().unwrap()).collect();
-
-        let [x, y, z] = nums[..3] else {
-            panic!("expected 3 integers")
+    fn parse_str(s: &str) -> Result<Self, String> {
+        let nums: Vec<i64> = s
+            .split(',')
+            .map(|s| s.parse::<i64>().map_err(|e| format!("invalid number {}: {}", s, e)))
+            .collect::<Result<_, _>>()?;
+
+        let [x, y, z] = nums[..] else {
+            return Err("expected 3 integers".to_string());
         };
 
-        Self { x, y, z }
+        Ok(Self { x, y, z })
     }
 
     fn add_x(&self, d: i64) -> Self {
@@ -104,42 +109,55 @@ impl Brick {
         false
     }
 
-    fn from_points(from: Point, to: Point) -> Self {
+    fn from_points(from: Point, to: Point) -> Result<Self, String> {
         let dx = to.x - from.x;
         let dy = to.y - from.y;
         let dz = to.z - from.z;
 
         match (dx, dy, dz) {
-            (0, 0, 0) => Brick::new(from, Axis::X, 0),
-            (dx, 0, 0) => Brick::new(min_by(from, to, |p| p.x), Axis::X, dx.unsigned_abs()),
-            (0, dy, 0) => Brick::new(min_by(from, to, |p| p.y), Axis::Y, dy.unsigned_abs()),
-            (0, 0, dz) => Brick::new(min_by(from, to, |p| p.z), Axis::Z, dz.unsigned_abs()),
-            _ => panic!("only one axis may change"),
+            (0, 0, 0) => Ok(Brick::new(from, Axis::X, 0)),
+            (dx, 0, 0) => Ok(Brick::new(min_by(from, to, |p| p.x), Axis::X, dx.unsigned_abs())),
+            (0, dy, 0) => Ok(Brick::new(min_by(from, to, |p| p.y), Axis::Y, dy.unsigned_abs())),
+            (0, 0, dz) => Ok(Brick::new(min_by(from, to, |p| p.z), Axis::Z, dz.unsigned_abs())),
+            _ => Err("only one axis may change".to_string()),
         }
     }
 }
 
-pub fn run(lines: Vec<&str>, chain_reaction: bool) -> u64 {
-    let mut bricks = Vec::new();
+fn parse_brick(line: &str) -> Result<Brick, String> {
+    let (from_str, to_str) = line.split_once('~').ok_or("missing '~'")?;
+    let from = Point::parse_str(from_str)?;
+    let to = Point::parse_str(to_str)?;
 
-    for line in lines {
-        let (from_str, to_str) = line.split_once('~').unwrap();
-        let from = Point::parse_str(from_str);
-        let to = Point::parse_str(to_str);
+    Brick::from_points(from, to)
+}
 
-        bricks.push(Brick::from_points(from, to));
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no bricks".to_string());
+    }
+    for line in lines {
+        let brick = parse_brick(line)?;
+        if brick.start.z < 1 {
+            return Err("bricks must be above the ground".to_string());
+        }
     }
+    Ok(())
+}
 
+/// Lets the bricks fall until they all rest on the ground or on other bricks.
+fn settle(bricks: &mut [Brick], budget: &Budget) -> Result<(), TimedOut<u64>> {
     let mut spots: HashSet<Point> = HashSet::new();
-    for b in &bricks {
+    for b in bricks.iter() {
         for p in b.points() {
             spots.insert(p);
         }
     }
 
     loop {
+        budget.check(|| 0)?;
         let mut moved = false;
-        for b in &mut bricks {
+        for b in bricks.iter_mut() {
             if b.start.z <= 1 {
                 // already on the ground
                 continue;
@@ -173,12 +191,16 @@ pub fn run(lines: Vec<&str>, chain_reaction: bool) -> u64 {
         }
     }
 
-    let bricks = bricks; // remove mut
-
     if let Some(b) = bricks.iter().find(|b| b.will_fall(&mut spots)) {
         panic!("brick can still fall: {:?}", b);
     }
 
+    Ok(())
+}
+
+/// For every brick the bricks directly above it, and the ones directly below it.
+/// Bricks on the ground depend on an extra brick with the index `bricks.len()`.
+fn supports(bricks: &[Brick]) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
     let mut dependents: Vec<Vec<usize>> = (0..bricks.len()).map(|_| Vec::new()).collect();
     let mut dependencies: Vec<Vec<usize>> = (0..bricks.len()).map(|_| Vec::new()).collect();
 
@@ -197,12 +219,30 @@ pub fn run(lines: Vec<&str>, chain_reaction: bool) -> u64 {
         }
     }
 
-    let dependents = dependents;
-    let dependencies = dependencies;
+    (dependents, dependencies)
+}
+
+pub fn run(lines: Vec<&str>, chain_reaction: bool) -> u64 {
+    run_with_budget(lines, chain_reaction, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the number of bricks whose removal has been fully analysed.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    chain_reaction: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<u64>> {
+    let mut bricks: Vec<Brick> = lines.into_iter().map(|l| parse_brick(l).unwrap()).collect();
+
+    settle(&mut bricks, budget)?;
+    let bricks = bricks; // remove mut
+
+    let (dependents, dependencies) = supports(&bricks);
 
     let mut num: Vec<u64> = std::iter::repeat_n(0, bricks.len()).collect();
 
     for (s_idx, _) in bricks.iter().enumerate() {
+        budget.check(|| s_idx as u64)?;
         let mut dependencies = dependencies.clone();
 
         dependencies[s_idx].clear(); // make this brick removable
@@ -232,8 +272,25 @@ pub fn run(lines: Vec<&str>, chain_reaction: bool) -> u64 {
     }
 
     if chain_reaction {
-        num.into_iter().sum()
+        Ok(num.into_iter().sum())
     } else {
-        num.into_iter().filter(|&n| n == 0).count() as u64
+        Ok(num.into_iter().filter(|&n| n == 0).count() as u64)
     }
 }
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut bricks: Vec<Brick> = lines.into_iter().map(|l| parse_brick(l).unwrap()).collect();
+    settle(&mut bricks, &Budget::unlimited()).unwrap();
+    let (dependents, dependencies) = supports(&bricks);
+
+    let settled = bricks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| format!("{}: {:?} {:?} {}\n", i, b.start, b.axis, b.dist))
+        .collect();
+    let supports = (0..bricks.len())
+        .map(|i| format!("{}: above {:?}, below {:?}\n", i, dependents[i], dependencies[i]))
+        .collect();
+
+    vec![("settled", settled), ("supports", supports)]
+}
@@ -41,6 +41,8 @@ mod dir {
 
 use dir::*;
 
+use crate::budget::{Budget, TimedOut};
+
 struct BasicGraph {
     width: usize,
     height: usize,
@@ -230,20 +232,44 @@ impl Graph {
     }
 }
 
-struct Search {
+/// How many nodes the search visits between looking at the clock.
+const NODES_PER_BUDGET_CHECK: u64 = 4096;
+
+struct Search<'b> {
     graph: Graph,
     visited: Vec<bool>,
+    budget: &'b Budget,
+    nodes_searched: u64,
+    best_found: Option<u16>, // longest complete path so far, reported if the budget runs out
 }
 
-impl Search {
-    fn new(graph: Graph) -> Self {
+impl<'b> Search<'b> {
+    fn new(graph: Graph, budget: &'b Budget) -> Self {
         let visited = std::iter::repeat_n(false, graph.nodes.len()).collect();
-        Self { graph, visited }
+        Self {
+            graph,
+            visited,
+            budget,
+            nodes_searched: 0,
+            best_found: None,
+        }
     }
 
-    fn find_longest_path(&mut self, src: u16, dst: u16) -> Option<u16> {
+    fn find_longest_path(
+        &mut self,
+        src: u16,
+        dst: u16,
+        walked: u16,
+    ) -> Result<Option<u16>, TimedOut<Option<u64>>> {
         if src == dst {
-            return Some(0);
+            if self.best_found.is_none_or(|b| b < walked) {
+                self.best_found = Some(walked);
+            }
+            return Ok(Some(0));
+        }
+        self.nodes_searched += 1;
+        if self.nodes_searched.is_multiple_of(NODES_PER_BUDGET_CHECK) {
+            self.budget.check(|| self.best_found.map(|b| b as u64))?;
         }
 
         let mut best: Option<u16> = None;
@@ -254,21 +280,43 @@ impl Search {
             let (neighbor, dist) = edges[n_idx as usize];
             if !self.visited[neighbor as usize] {
                 self.visited[neighbor as usize] = true;
-                if let Some(res) = self.find_longest_path(neighbor, dst) {
+                let res = self.find_longest_path(neighbor, dst, walked + dist);
+                self.visited[neighbor as usize] = false;
+                if let Some(res) = res? {
                     let res = res + dist;
                     if best.is_none() || best.unwrap() < res {
                         best = Some(res);
                     }
                 }
-                self.visited[neighbor as usize] = false;
             }
         }
 
-        best
+        Ok(best)
+    }
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let width = lines.first().ok_or("no lines")?.len();
+    if lines.iter().any(|line| line.len() != width) {
+        return Err("lines have different lengths".to_string());
+    }
+    if lines[0].matches('.').count() != 1 || lines[lines.len() - 1].matches('.').count() != 1 {
+        return Err("expected one entrance and one exit".to_string());
     }
+    BasicGraph::from_lines(lines, false)?;
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, ignore_slopes: bool) -> u64 {
+    run_with_budget(lines, ignore_slopes, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the longest path found so far.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    ignore_slopes: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<Option<u64>>> {
     let graph = BasicGraph::from_lines(&lines, ignore_slopes).unwrap();
 
     let graph = graph.as_compact_graph();
@@ -276,6 +324,25 @@ pub fn run(lines: Vec<&str>, ignore_slopes: bool) -> u64 {
     let src = 0;
     let dst = (graph.nodes.len() - 1) as u16;
 
-    let mut search = Search::new(graph);
-    search.find_longest_path(src, dst).unwrap() as u64
+    let mut search = Search::new(graph, budget);
+    Ok(search.find_longest_path(src, dst, 0)?.unwrap() as u64)
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut res = Vec::new();
+    for (name, ignore_slopes) in [("graph_slopes", false), ("graph_no_slopes", true)] {
+        let graph = BasicGraph::from_lines(&lines, ignore_slopes)
+            .unwrap()
+            .as_compact_graph();
+
+        let mut text = String::new();
+        for (i, node) in graph.nodes.iter().enumerate() {
+            if node.num_edges > 0 {
+                let edges = &node.edges[..node.num_edges as usize];
+                text += &format!("{}: {:?}\n", i, edges);
+            }
+        }
+        res.push((name, text));
+    }
+    res
 }
@@ -2,6 +2,8 @@ use std::ops::Sub;
 
 use num::Integer;
 
+use crate::budget::{Budget, TimedOut};
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Vec3 {
     x: i64,
@@ -162,24 +164,34 @@ fn verify_rays_converge(rays: &[Ray], a: Ray, ts: &[i64]) {
     }
 }
 
-fn parse_vec3(s: &str) -> Option<Vec3> {
-    let mut ns = s.split(',').map(|s| s.trim().parse().unwrap());
-    Some(Vec3::new(ns.next()?, ns.next()?, ns.next()?))
+fn parse_vec3(s: &str) -> Result<Vec3, String> {
+    let ns: Vec<i64> = s
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|e| format!("invalid number {}: {}", s, e)))
+        .collect::<Result<_, _>>()?;
+    let [x, y, z] = ns[..] else {
+        return Err("expected 3 integers".to_string());
+    };
+    Ok(Vec3::new(x, y, z))
 }
 
-fn parse_input(lines: &[&str]) -> Vec<Ray> {
+fn parse_input(lines: &[&str]) -> Result<Vec<Ray>, String> {
     lines
         .iter()
         .map(|line| {
-            let (pos, vel) = line.split_once('@').unwrap();
-            Ray::new(
-                parse_vec3(pos.trim()).unwrap(),
-                parse_vec3(vel.trim()).unwrap(),
-            )
+            let (pos, vel) = line.split_once('@').ok_or("missing '@'")?;
+            Ok(Ray::new(parse_vec3(pos.trim())?, parse_vec3(vel.trim())?))
         })
         .collect()
 }
 
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if parse_input(lines)?.len() < 3 {
+        return Err("expected at least three hailstones".to_string());
+    }
+    Ok(())
+}
+
 fn find_xy_intersection(r1: &Ray, r2: &Ray) -> Option<(f64, f64)> {
     let Ray { start: s1, dir: v1 } = *r1;
     let Ray { start: s2, dir: v2 } = *r2;
@@ -213,11 +225,24 @@ fn find_xy_intersection(r1: &Ray, r2: &Ray) -> Option<(f64, f64)> {
 }
 
 pub fn run(lines: Vec<&str>, lo: i64, hi: i64, part2: bool) -> u64 {
-    let rays = parse_input(&lines);
+    run_with_budget(lines, lo, hi, part2, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the number of crossings found so far in part 1,
+/// and the largest velocity (per axis) that has been fully searched in part 2.
+pub fn run_with_budget(
+    lines: Vec<&str>,
+    lo: i64,
+    hi: i64,
+    part2: bool,
+    budget: &Budget,
+) -> Result<u64, TimedOut<u64>> {
+    let rays = parse_input(&lines).unwrap();
 
     if !part2 {
         let mut count = 0;
         for i in 0..rays.len() {
+            budget.check(|| count)?;
             for j in (i + 1)..rays.len() {
                 if let Some((x, y)) = find_xy_intersection(&rays[i], &rays[j]) {
                     let lo = lo as f64;
@@ -228,11 +253,12 @@ pub fn run(lines: Vec<&str>, lo: i64, hi: i64, part2: bool) -> u64 {
                 }
             }
         }
-        count
+        Ok(count)
     } else {
         // check all velocities (up to 1000), but start with small ones
         for d in 0..=1000 {
             for i in -d..=d {
+                budget.check(|| (d - 1).max(0) as u64)?;
                 for j in -d..=d {
                     for (dx, dy, dz) in [
                         (d, i, j),
@@ -246,7 +272,7 @@ pub fn run(lines: Vec<&str>, lo: i64, hi: i64, part2: bool) -> u64 {
                         if let Some(ts) = calculate_collision_times(&rays, v) {
                             let s = Ray::new(rays[0].evaulate(ts[0]), v).evaulate(-ts[0]);
                             verify_rays_converge(&rays, Ray::new(s, v), &ts); // just to be sure
-                            return (s.x + s.y + s.z) as u64;
+                            return Ok((s.x + s.y + s.z) as u64);
                         }
                     }
                 }
@@ -256,6 +282,25 @@ pub fn run(lines: Vec<&str>, lo: i64, hi: i64, part2: bool) -> u64 {
     }
 }
 
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let rays = parse_input(&lines).unwrap();
+
+    let parsed = rays.iter().map(|r| format!("{:?}\n", r)).collect();
+
+    let mut intersections = String::new();
+    for i in 0..rays.len() {
+        for j in (i + 1)..rays.len() {
+            let res = match find_xy_intersection(&rays[i], &rays[j]) {
+                Some((x, y)) => format!("({:.3}, {:.3})", x, y),
+                None => "none".to_string(),
+            };
+            intersections += &format!("{} x {}: {}\n", i, j, res);
+        }
+    }
+
+    vec![("rays", parsed), ("xy_intersections", intersections)]
+}
+
 #[cfg(test)]
 mod tests {
     use assert_approx_eq::assert_approx_eq;
@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use priority_queue::PriorityQueue;
 
+use crate::budget::{Budget, TimedOut};
+
 struct NameCache<'a> {
     indices: HashMap<&'a str, usize>,
     names: Vec<&'a str>,
@@ -46,8 +48,8 @@ impl Graph {
         }
     }
 
-    fn min_cut(&self) -> MinCut {
-        StoerWagner::init(self.clone()).calculate()
+    fn min_cut(&self, budget: &Budget) -> Result<MinCut, TimedOut<Option<u64>>> {
+        StoerWagner::init(self.clone()).calculate(budget)
     }
 
     fn merge_nodes(&mut self, s: usize, t: usize) {
@@ -76,6 +78,7 @@ impl Graph {
     }
 }
 
+#[derive(Debug)]
 struct MinCut {
     cut_weight: u64,
     split_size: usize,
@@ -91,12 +94,13 @@ impl StoerWagner {
         Self { graph }
     }
 
-    fn calculate(mut self) -> MinCut {
+    fn calculate(mut self, budget: &Budget) -> Result<MinCut, TimedOut<Option<u64>>> {
         let mut best_cut = usize::MAX;
         let mut best_cut_graph_size: Option<usize> = None;
 
         let mut num_nodes_left = self.graph.nodes.len();
         while num_nodes_left > 1 {
+            budget.check(|| (best_cut != usize::MAX).then_some(best_cut as u64))?;
             let (s, t) = self.min_cut_phase();
             let cut = self.graph.nodes[t].len();
             let cut_graph_size = self.graph.node_cardinalities[t];
@@ -110,10 +114,10 @@ impl StoerWagner {
             num_nodes_left -= 1;
         }
 
-        MinCut {
+        Ok(MinCut {
             cut_weight: best_cut as u64,
             split_size: best_cut_graph_size.unwrap(),
-        }
+        })
     }
 
     fn min_cut_phase(&self) -> (usize, usize) {
@@ -142,13 +146,16 @@ impl StoerWagner {
     }
 }
 
-pub fn run(lines: Vec<&str>) -> u64 {
+fn parse_nodes(lines: &[&str]) -> Result<Vec<Vec<usize>>, String> {
     let mut names = NameCache::new();
 
     let mut nodes: Vec<Vec<usize>> = Vec::new();
 
     for line in lines {
-        let (l_str, rs) = line.split_once(':').unwrap();
+        let (l_str, rs) = line.split_once(':').ok_or("missing ':'")?;
+        if l_str.is_empty() || rs.trim().is_empty() {
+            return Err(format!("expected 'name: name...': {}", line));
+        }
         let l = names.insert(l_str);
 
         if l == nodes.len() {
@@ -167,6 +174,24 @@ pub fn run(lines: Vec<&str>) -> u64 {
         }
     }
 
+    Ok(nodes)
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if parse_nodes(lines)?.len() < 2 {
+        return Err("expected at least two components".to_string());
+    }
+    Ok(())
+}
+
+pub fn run(lines: Vec<&str>) -> u64 {
+    run_with_budget(lines, &Budget::unlimited()).unwrap()
+}
+
+/// On time out the partial result is the smallest cut found so far, if any.
+pub fn run_with_budget(lines: Vec<&str>, budget: &Budget) -> Result<u64, TimedOut<Option<u64>>> {
+    let nodes = parse_nodes(&lines).unwrap();
+
     let num_nodes = nodes.len();
 
     let graph = Graph::new(nodes);
@@ -174,9 +199,22 @@ pub fn run(lines: Vec<&str>) -> u64 {
     let MinCut {
         cut_weight,
         split_size,
-    } = graph.min_cut();
+    } = graph.min_cut(budget)?;
 
     assert_eq!(cut_weight, 3);
 
-    split_size as u64 * (num_nodes - split_size) as u64
+    Ok(split_size as u64 * (num_nodes - split_size) as u64)
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let nodes = parse_nodes(&lines).unwrap();
+
+    let adjacency = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| format!("{}: {:?}\n", i, n))
+        .collect();
+    let min_cut = Graph::new(nodes).min_cut(&Budget::unlimited()).unwrap();
+
+    vec![("adjacency", adjacency), ("min_cut", format!("{:?}\n", min_cut))]
 }
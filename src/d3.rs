@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::RangeInclusive};
 
 fn parse_symbols(lines: &[&str]) -> HashMap<(usize, usize), char> {
     let mut symbols = HashMap::new();
@@ -12,12 +12,12 @@ fn parse_symbols(lines: &[&str]) -> HashMap<(usize, usize), char> {
     symbols
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct Number {
-    x: usize,
-    y: usize,
-    digits: usize,
-    value: u32,
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Number {
+    pub x: usize,
+    pub y: usize,
+    pub digits: usize,
+    pub value: u32,
 }
 
 fn parse_numbers(lines: &[&str]) -> Vec<Number> {
@@ -54,58 +54,243 @@ fn parse_numbers(lines: &[&str]) -> Vec<Number> {
     numbers
 }
 
-pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
-    let symbols = parse_symbols(&lines);
-    let numbers = parse_numbers(&lines);
+/// Checks that the lines form a rectangular schematic with at least one number and one symbol.
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let width = lines.first().ok_or("no lines")?.len();
+    for line in lines {
+        if line.len() != width {
+            return Err("lines have different lengths".to_string());
+        }
+        if let Some(c) = line.chars().find(|c| !c.is_ascii_graphic()) {
+            return Err(format!("invalid character: {:?}", c));
+        }
+        if line
+            .split(|c: char| !c.is_ascii_digit())
+            .any(|digits| digits.len() > 9)
+        {
+            return Err("number is too long".to_string());
+        }
+    }
+    if parse_numbers(lines).is_empty() {
+        return Err("no numbers found".to_string());
+    }
+    if parse_symbols(lines).is_empty() {
+        return Err("no symbols found".to_string());
+    }
+    Ok(())
+}
 
-    if part1 {
-        let mut total = 0;
-        for num in numbers {
-            let mut found = false;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Symbol {
+    pub x: usize,
+    pub y: usize,
+    pub ch: char,
+}
+
+/// Which symbols a query looks at.
+#[derive(Debug, Clone, Copy)]
+pub enum SymbolClass {
+    Any,
+    Only(char),
+}
+
+impl SymbolClass {
+    fn matches(self, ch: char) -> bool {
+        match self {
+            SymbolClass::Any => true,
+            SymbolClass::Only(c) => c == ch,
+        }
+    }
+}
+
+/// How the numbers around a gear are combined into one value.
+#[derive(Debug, Clone, Copy)]
+pub enum Combiner {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Combiner {
+    /// Combining no values gives 0 for every combiner.
+    pub fn combine(self, values: impl Iterator<Item = u32>) -> u64 {
+        let values = values.map(|v| v as u64);
+        match self {
+            Combiner::Product => values.reduce(|a, b| a * b).unwrap_or(0),
+            Combiner::Sum => values.sum(),
+            Combiner::Max => values.max().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Gear {
+    pub symbol: Symbol,
+    pub numbers: Vec<Number>,
+}
+
+/// Numbers and symbols, and which of them touch each other (also diagonally).
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    number_symbols: Vec<Vec<usize>>, // indices into `symbols` for every number
+    symbol_numbers: Vec<Vec<usize>>, // indices into `numbers` for every symbol
+}
+
+impl Schematic {
+    pub fn new(lines: &[&str]) -> Self {
+        let mut symbols: Vec<Symbol> = parse_symbols(lines)
+            .into_iter()
+            .map(|((x, y), ch)| Symbol { x, y, ch })
+            .collect();
+        symbols.sort_by_key(|s| (s.y, s.x));
+        let numbers = parse_numbers(lines);
+
+        let symbol_indices: HashMap<(usize, usize), usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ((s.x, s.y), i))
+            .collect();
+
+        let mut number_symbols = vec![Vec::new(); numbers.len()];
+        let mut symbol_numbers = vec![Vec::new(); symbols.len()];
+        for (n_idx, num) in numbers.iter().enumerate() {
             for dy in -1..=1 {
                 for dx in -1..=num.digits as isize {
                     let nx = num.x as isize + dx;
                     let ny = num.y as isize + dy;
-                    if nx >= 0 && ny >= 0 && symbols.contains_key(&(nx as usize, ny as usize)) {
-                        total += num.value;
-                        found = true;
-                        break;
+                    if nx >= 0
+                        && ny >= 0
+                        && let Some(&s_idx) = symbol_indices.get(&(nx as usize, ny as usize))
+                    {
+                        number_symbols[n_idx].push(s_idx);
+                        symbol_numbers[s_idx].push(n_idx);
                     }
                 }
-                if found {
-                    break;
-                }
             }
         }
-        total
-    } else {
-        let mut total: u32 = 0;
-        let mut gear_number_counts: HashMap<(usize, usize), u32> = HashMap::new();
-        let mut gear_ratios: HashMap<(usize, usize), u32> = HashMap::new();
-        for num in numbers {
-            for dy in -1..=1 {
-                for dx in -1..=num.digits as isize {
-                    let nx = num.x as isize + dx;
-                    let ny = num.y as isize + dy;
-                    let coords = (nx as usize, ny as usize);
-                    if nx >= 0 && ny >= 0 && symbols.get(&coords) == Some(&'*') {
-                        gear_number_counts
-                            .insert(coords, gear_number_counts.get(&coords).unwrap_or(&0) + 1);
-                        gear_ratios
-                            .insert(coords, gear_ratios.get(&coords).unwrap_or(&1) * num.value);
+
+        Self {
+            numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
+        }
+    }
+
+    /// The numbers that touch at least one symbol of the class.
+    pub fn part_numbers(&self, class: SymbolClass) -> Vec<&Number> {
+        self.numbers
+            .iter()
+            .zip(self.number_symbols.iter())
+            .filter(|(_, syms)| syms.iter().any(|&s| class.matches(self.symbols[s].ch)))
+            .map(|(num, _)| num)
+            .collect()
+    }
+
+    /// The symbols of the class that touch the given number of numbers, ordered by position.
+    pub fn gears(&self, class: SymbolClass, neighbours: RangeInclusive<usize>) -> Vec<Gear> {
+        self.symbols
+            .iter()
+            .zip(self.symbol_numbers.iter())
+            .filter(|(sym, nums)| class.matches(sym.ch) && neighbours.contains(&nums.len()))
+            .map(|(&symbol, nums)| Gear {
+                symbol,
+                numbers: nums.iter().map(|&n| self.numbers[n].clone()).collect(),
+            })
+            .collect()
+    }
+
+    pub fn gear_total(
+        &self,
+        class: SymbolClass,
+        neighbours: RangeInclusive<usize>,
+        combiner: Combiner,
+    ) -> u64 {
+        self.gears(class, neighbours)
+            .iter()
+            .map(|g| combiner.combine(g.numbers.iter().map(|n| n.value)))
+            .sum()
+    }
+
+    /// Groups of numbers (as indices into `numbers`) that are linked through shared symbols.
+    /// Numbers that touch no symbol form groups of their own.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.numbers.len()];
+        let mut res = Vec::new();
+
+        for start in 0..self.numbers.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(n) = stack.pop() {
+                component.push(n);
+                for &s in self.number_symbols[n].iter() {
+                    for &other in self.symbol_numbers[s].iter() {
+                        if !seen[other] {
+                            seen[other] = true;
+                            stack.push(other);
+                        }
                     }
                 }
             }
+            component.sort();
+            res.push(component);
         }
-        for ((x, y), count) in gear_number_counts {
-            if count == 2 {
-                total += gear_ratios.get(&(x, y)).unwrap();
-            }
-        }
-        total
+
+        res
     }
 }
 
+pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
+    let schematic = Schematic::new(&lines);
+
+    if part1 {
+        schematic
+            .part_numbers(SymbolClass::Any)
+            .iter()
+            .map(|n| n.value)
+            .sum()
+    } else {
+        schematic.gear_total(SymbolClass::Only('*'), 2..=2, Combiner::Product) as u32
+    }
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut symbols: Vec<((usize, usize), char)> = parse_symbols(&lines).into_iter().collect();
+    symbols.sort();
+    let symbols = symbols
+        .into_iter()
+        .map(|((x, y), ch)| format!("({}, {}) {}\n", x, y, ch))
+        .collect();
+    let numbers = format!("{:#?}\n", parse_numbers(&lines));
+
+    let schematic = Schematic::new(&lines);
+    let gears = schematic
+        .gears(SymbolClass::Any, 1..=8)
+        .iter()
+        .map(|g| {
+            let values: Vec<u32> = g.numbers.iter().map(|n| n.value).collect();
+            format!("({}, {}) {}: {:?}\n", g.symbol.x, g.symbol.y, g.symbol.ch, values)
+        })
+        .collect();
+    let components = schematic
+        .components()
+        .iter()
+        .map(|c| format!("{:?}\n", c))
+        .collect();
+
+    vec![
+        ("symbols", symbols),
+        ("numbers", numbers),
+        ("gears", gears),
+        ("components", components),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use crate::d3::Number;
@@ -1,81 +1,215 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
+#[derive(Debug)]
 struct Card {
     id: u32,
     winning: Vec<i32>,
     have: Vec<i32>,
 }
 
-impl From<&str> for Card {
-    fn from(line: &str) -> Self {
-        let (start, rest) = line.split_once(':').unwrap();
-        let (left, right) = rest.split_once('|').unwrap();
-        Card {
-            id: start[5..].trim().parse().unwrap(),
-            winning: parse_numbers(left),
-            have: parse_numbers(right),
-        }
+impl TryFrom<&str> for Card {
+    type Error = String;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        let (start, rest) = line.split_once(':').ok_or("missing ':'")?;
+        let (left, right) = rest.split_once('|').ok_or("missing '|'")?;
+        let id = start
+            .strip_prefix("Card ")
+            .ok_or_else(|| format!("expected 'Card <id>': {}", start))?
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid card id: {}", e))?;
+        Ok(Card {
+            id,
+            winning: parse_numbers(left)?,
+            have: parse_numbers(right)?,
+        })
     }
 }
 
-fn parse_numbers(line: &str) -> Vec<i32> {
+fn parse_numbers(line: &str) -> Result<Vec<i32>, String> {
     line.trim()
         .split(' ')
         .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap())
+        .map(|s| s.parse().map_err(|e| format!("invalid number {}: {}", s, e)))
         .collect()
 }
 
-pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
-    if part1 {
-        let mut total = 0;
-        for line in lines {
-            let card = Card::from(line);
-            let mut s = 0;
-            for n in card.have {
-                if card.winning.contains(&n) {
-                    if s == 0 {
-                        s = 1;
-                    } else {
-                        s *= 2;
-                    }
-                }
-            }
-            total += s;
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no cards".to_string());
+    }
+    for &line in lines {
+        Card::try_from(line)?;
+    }
+    Ok(())
+}
+
+impl Card {
+    fn matches(&self) -> usize {
+        self.have.iter().filter(|n| self.winning.contains(n)).count()
+    }
+}
+
+/// How many points a card with the given number of matches is worth.
+#[derive(Debug, Clone, Copy)]
+pub enum Scoring {
+    /// One point for the first match, doubled for every further match.
+    Doubling,
+    /// One point per match.
+    Linear,
+    Custom(fn(usize) -> u64),
+}
+
+impl Scoring {
+    pub fn points(self, matches: usize) -> Result<u64, String> {
+        match self {
+            Scoring::Doubling if matches == 0 => Ok(0),
+            Scoring::Doubling => 1u64
+                .checked_shl(matches as u32 - 1)
+                .ok_or_else(|| format!("too many matches: {}", matches)),
+            Scoring::Linear => Ok(matches as u64),
+            Scoring::Custom(f) => Ok(f(matches)),
         }
-        total
-    } else {
-        let mut total: u32 = 0;
+    }
+}
+
+/// Which later cards a card wins copies of.
+#[derive(Debug, Clone)]
+pub enum CopyRule {
+    /// A card with `m` matches wins a copy of each of the next `m` cards.
+    Following,
+    /// The `k`th match wins a copy of the card `offsets[k]` ahead.
+    /// Matches beyond the end of the list win nothing.
+    Offsets(Vec<usize>),
+}
 
-        let mut cards = HashMap::new();
-        for line in lines {
-            let card = Card::from(line);
-            cards.insert(card.id, card);
+/// What happens to copies of cards after the last card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PastEnd {
+    Clamp,
+    Error,
+}
+
+/// Counts the cards as they are played, only keeping the copies won for the cards still ahead.
+pub struct Cascade {
+    rule: CopyRule,
+    past_end: PastEnd,
+    pending: VecDeque<u64>, // copies won so far of the next cards
+    last_id: Option<u32>,
+    total: u64,
+}
+
+impl Cascade {
+    pub fn new(rule: CopyRule, past_end: PastEnd) -> Self {
+        Self {
+            rule,
+            past_end,
+            pending: VecDeque::new(),
+            last_id: None,
+            total: 0,
         }
-        let mut ids: Vec<u32> = cards.keys().copied().collect();
-        ids.sort();
-
-        let mut count: HashMap<u32, u32> = ids.iter().map(|&id| (id, 1)).collect();
-
-        for id in ids {
-            let card = cards.remove(&id).unwrap();
-            let mut m = 0;
-            for n in card.have {
-                if card.winning.contains(&n) {
-                    m += 1;
-                }
-            }
+    }
+
+    /// Plays the card, which must directly follow the previous one, and returns how many
+    /// instances of it there are.
+    pub fn push(&mut self, id: u32, matches: usize) -> Result<u64, String> {
+        if let Some(last) = self.last_id
+            && last.checked_add(1) != Some(id)
+        {
+            return Err(format!("card {} does not follow card {}", id, last));
+        }
+        self.last_id = Some(id);
 
-            let c = count[&id];
-            total += c;
+        let instances = 1 + self.pending.pop_front().unwrap_or(0);
+        self.total = self.total.checked_add(instances).ok_or("too many cards")?;
 
-            if m > 0 {
-                for d in 1..=m {
-                    let i = id + d;
-                    count.insert(i, count[&i] + c);
-                }
+        let mut win = |offset: usize| -> Result<(), String> {
+            if offset == 0 {
+                return Err("a card can't win copies of itself".to_string());
             }
+            if self.pending.len() < offset {
+                self.pending.resize(offset, 0);
+            }
+            let p = &mut self.pending[offset - 1];
+            *p = p.checked_add(instances).ok_or("too many cards")?;
+            Ok(())
+        };
+        match &self.rule {
+            CopyRule::Following => (1..=matches).try_for_each(&mut win)?,
+            CopyRule::Offsets(offsets) => offsets.iter().take(matches).try_for_each(|&o| win(o))?,
         }
-        total
+
+        Ok(instances)
+    }
+
+    /// The total number of cards, checking what was won past the last card.
+    pub fn finish(self) -> Result<u64, String> {
+        if self.past_end == PastEnd::Error && self.pending.iter().any(|&p| p > 0) {
+            let last = self.last_id.unwrap_or(0);
+            return Err(format!("copies won of cards after the last card {}", last));
+        }
+        Ok(self.total)
+    }
+}
+
+pub fn total_points<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    scoring: Scoring,
+) -> Result<u64, String> {
+    let mut total: u64 = 0;
+    for line in lines {
+        let points = scoring.points(Card::try_from(line)?.matches())?;
+        total = total.checked_add(points).ok_or("too many points")?;
     }
+    Ok(total)
+}
+
+/// Runs the cascade in a single pass, the memory used only depends on how far ahead cards win.
+pub fn total_cards<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    rule: CopyRule,
+    past_end: PastEnd,
+) -> Result<u64, String> {
+    let mut cascade = Cascade::new(rule, past_end);
+    for line in lines {
+        let card = Card::try_from(line)?;
+        cascade.push(card.id, card.matches())?;
+    }
+    cascade.finish()
+}
+
+pub fn run(lines: Vec<&str>, part1: bool) -> u32 {
+    let total = if part1 {
+        total_points(lines, Scoring::Doubling)
+    } else {
+        total_cards(lines, CopyRule::Following, PastEnd::Error)
+    };
+    total.unwrap() as u32
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let cards: Vec<Card> = lines
+        .iter()
+        .map(|&line| Card::try_from(line).unwrap())
+        .collect();
+    let matches = cards
+        .iter()
+        .map(|card| format!("card {}: {} matches\n", card.id, card.matches()))
+        .collect();
+
+    let mut cascade = Cascade::new(CopyRule::Following, PastEnd::Clamp);
+    let instances = cards
+        .iter()
+        .map(|card| {
+            let n = cascade.push(card.id, card.matches()).unwrap();
+            format!("card {}: {} instances\n", card.id, n)
+        })
+        .collect();
+
+    vec![
+        ("cards", format!("{:#?}\n", cards)),
+        ("matches", matches),
+        ("instances", instances),
+    ]
 }
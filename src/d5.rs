@@ -1,14 +1,26 @@
+use std::fmt::Display;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Range {
-    start: u64,
-    length: u64,
+pub struct Range {
+    pub start: u64,
+    pub length: u64,
 }
 
 impl Range {
-    fn new(start: u64, length: u64) -> Self {
+    pub fn new(start: u64, length: u64) -> Self {
         Self { start, length }
     }
 
+    fn end(&self) -> u64 {
+        self.start + self.length
+    }
+
+    fn intersection(&self, other: &Range) -> Option<Range> {
+        let l = self.start.max(other.start);
+        let r = self.end().min(other.end());
+        (l < r).then(|| Range::new(l, r - l))
+    }
+
     fn from_pairs(numbers: &[u64]) -> Vec<Range> {
         assert!(numbers.len().is_multiple_of(2));
 
@@ -61,19 +73,21 @@ impl Remap {
     }
 }
 
-impl From<&str> for Remap {
-    fn from(line: &str) -> Self {
-        let mut parts = line
+impl TryFrom<&str> for Remap {
+    type Error = String;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        let parts = line
             .split(' ')
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<u64>().unwrap())
-            .take(3);
+            .map(|s| s.parse::<u64>().map_err(|e| format!("invalid number {}: {}", s, e)))
+            .collect::<Result<Vec<u64>, String>>()?;
 
-        let dst_start = parts.next().unwrap();
-        let src_start = parts.next().unwrap();
-        let length = parts.next().unwrap();
+        let [dst_start, src_start, length] = parts[..] else {
+            return Err(format!("expected 3 numbers: {}", line));
+        };
 
-        Self::new(dst_start, src_start, length)
+        Ok(Self::new(dst_start, src_start, length))
     }
 }
 
@@ -112,6 +126,169 @@ impl Mapping {
 
         merge_ranges(res)
     }
+
+    /// Like `apply_ranges`, the first remap that covers a value wins.
+    fn to_piecewise(&self) -> Piecewise {
+        let mut breakpoints: Vec<u64> = self
+            .remaps
+            .iter()
+            .flat_map(|r| [r.src, r.src + r.length])
+            .chain([0, u64::MAX])
+            .collect();
+        breakpoints.sort();
+        breakpoints.dedup();
+
+        let pieces = breakpoints
+            .windows(2)
+            .map(|w| {
+                let remap = self
+                    .remaps
+                    .iter()
+                    .find(|r| r.src <= w[0] && w[0] < r.src + r.length);
+                let dst = match remap {
+                    Some(r) => w[0] - r.src + r.dst,
+                    None => w[0],
+                };
+                Piece {
+                    src: w[0],
+                    length: w[1] - w[0],
+                    dst,
+                }
+            })
+            .collect();
+        Piecewise::from_pieces(pieces)
+    }
+}
+
+/// Maps `src..src + length` to `dst..dst + length`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Piece {
+    src: u64,
+    length: u64,
+    dst: u64,
+}
+
+/// A function that is linear with slope 1 between its breakpoints, defined for `0..u64::MAX`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Piecewise {
+    pieces: Vec<Piece>, // ordered by `src` and without gaps
+}
+
+impl Piecewise {
+    pub fn identity() -> Self {
+        Self::from_pieces(vec![Piece {
+            src: 0,
+            length: u64::MAX,
+            dst: 0,
+        }])
+    }
+
+    /// Joins neighbouring pieces that continue each other.
+    fn from_pieces(pieces: Vec<Piece>) -> Self {
+        let mut res: Vec<Piece> = Vec::new();
+        for p in pieces {
+            if let Some(last) = res.last_mut()
+                && last.src + last.length == p.src
+                && last.dst.checked_add(last.length) == Some(p.dst)
+            {
+                last.length += p.length;
+            } else {
+                res.push(p);
+            }
+        }
+        Self { pieces: res }
+    }
+
+    pub fn evaluate(&self, x: u64) -> u64 {
+        let idx = self.pieces.partition_point(|p| p.src + p.length <= x);
+        let p = self.pieces[idx.min(self.pieces.len() - 1)];
+        x - p.src + p.dst
+    }
+
+    /// This function followed by `next`.
+    pub fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut pieces = Vec::new();
+        for p in self.pieces.iter() {
+            for &(dst_range, n) in next.overlapping(Range::new(p.dst, p.length)).iter() {
+                pieces.push(Piece {
+                    src: dst_range.start - p.dst + p.src,
+                    length: dst_range.length,
+                    dst: dst_range.start - n.src + n.dst,
+                });
+            }
+        }
+        Self::from_pieces(pieces)
+    }
+
+    /// The parts of the range that fall into each piece.
+    fn overlapping(&self, range: Range) -> Vec<(Range, Piece)> {
+        let end = range.start + range.length;
+        self.pieces
+            .iter()
+            .filter_map(|&p| {
+                let l = p.src.max(range.start);
+                let r = (p.src + p.length).min(end);
+                (l < r).then(|| (Range::new(l, r - l), p))
+            })
+            .collect()
+    }
+
+    /// The values where a new piece starts.
+    pub fn breakpoints(&self) -> Vec<u64> {
+        self.pieces.iter().map(|p| p.src).collect()
+    }
+
+    /// All ranges of values that are mapped into the target range.
+    pub fn preimage(&self, target: Range) -> Vec<Range> {
+        let end = target.start + target.length;
+        let ranges = self
+            .pieces
+            .iter()
+            .filter_map(|p| {
+                let l = p.dst.max(target.start);
+                let r = (p.dst + p.length).min(end);
+                (l < r).then(|| Range::new(l - p.dst + p.src, r - l))
+            })
+            .collect();
+        merge_ranges(ranges)
+    }
+
+    /// All values that are mapped to `y`.
+    pub fn inverse(&self, y: u64) -> Vec<u64> {
+        // one value per piece, merging them as ranges would lose neighbouring values
+        let mut xs: Vec<u64> = self
+            .pieces
+            .iter()
+            .filter(|p| p.dst <= y && y - p.dst < p.length)
+            .map(|p| y - p.dst + p.src)
+            .collect();
+        xs.sort();
+        xs
+    }
+
+    /// The smallest value the range is mapped to.
+    pub fn min_over(&self, range: Range) -> Option<u64> {
+        self.overlapping(range)
+            .iter()
+            .map(|(r, p)| r.start - p.src + p.dst)
+            .min()
+    }
+}
+
+impl Display for Piecewise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for p in self.pieces.iter() {
+            writeln!(
+                f,
+                "[{}, {}) -> [{}, {})",
+                p.src,
+                p.src + p.length,
+                p.dst,
+                p.dst + p.length
+            )?;
+        }
+        Ok(())
+    }
 }
 
 fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
@@ -145,21 +322,53 @@ fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
     res
 }
 
-impl From<&[&str]> for Mapping {
-    fn from(lines: &[&str]) -> Self {
-        Self {
-            remaps: lines.iter().map(|&line| Remap::from(line)).collect(),
-        }
+impl TryFrom<&[&str]> for Mapping {
+    type Error = String;
+
+    fn try_from(lines: &[&str]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            remaps: lines
+                .iter()
+                .map(|&line| Remap::try_from(line))
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Almanac {
+pub struct Almanac {
     seeds: Vec<Range>,
     mappings: Vec<Mapping>,
 }
 
 impl Almanac {
+    pub fn seeds(&self) -> &[Range] {
+        &self.seeds
+    }
+
+    /// All maps composed into one function from seed to location.
+    pub fn composed(&self) -> Piecewise {
+        self.mappings
+            .iter()
+            .fold(Piecewise::identity(), |f, m| f.then(&m.to_piecewise()))
+    }
+
+    /// The seeds of the almanac whose location falls into the range.
+    pub fn seeds_mapping_to(&self, locations: Range) -> Vec<Range> {
+        let candidates = self.composed().preimage(locations);
+        let mut res = Vec::new();
+        for seeds in self.seeds.iter() {
+            for c in candidates.iter() {
+                let l = seeds.start.max(c.start);
+                let r = (seeds.start + seeds.length).min(c.start + c.length);
+                if l < r {
+                    res.push(Range::new(l, r - l));
+                }
+            }
+        }
+        merge_ranges(res)
+    }
+
     fn locations(&self) -> Vec<Range> {
         let mut current = self.seeds.clone();
         for mapping in self.mappings.iter() {
@@ -169,7 +378,7 @@ impl Almanac {
     }
 }
 
-fn parse_almanac(lines: &[&str], seeds_as_ranges: bool) -> Almanac {
+pub fn parse_almanac(lines: &[&str], seeds_as_ranges: bool) -> Result<Almanac, String> {
     let mut almanac = Almanac {
         seeds: Vec::new(),
         mappings: Vec::new(),
@@ -179,7 +388,9 @@ fn parse_almanac(lines: &[&str], seeds_as_ranges: bool) -> Almanac {
     for line in lines {
         if line.is_empty() {
             if let Some(_name) = current_part {
-                almanac.mappings.push(Mapping::from(waiting_lines.as_ref()));
+                almanac
+                    .mappings
+                    .push(Mapping::try_from(waiting_lines.as_ref())?);
             }
             current_part = None;
             waiting_lines.clear();
@@ -188,31 +399,251 @@ fn parse_almanac(lines: &[&str], seeds_as_ranges: bool) -> Almanac {
                 let numbers: Vec<u64> = line
                     .split(' ')
                     .filter(|s| !s.is_empty())
-                    .map(|s| s.parse::<u64>().unwrap())
-                    .collect();
+                    .map(|s| s.parse::<u64>().map_err(|e| format!("invalid seed {}: {}", s, e)))
+                    .collect::<Result<_, _>>()?;
 
                 if seeds_as_ranges {
+                    if !numbers.len().is_multiple_of(2) {
+                        return Err("odd number of seed numbers".to_string());
+                    }
                     almanac.seeds.extend(Range::from_pairs(numbers.as_ref()))
                 } else {
                     almanac
                         .seeds
                         .extend(numbers.into_iter().map(|n| Range::new(n, 1)));
                 }
-            } else {
+            } else if line.ends_with(" map:") {
                 current_part = Some(line);
+            } else {
+                return Err(format!("expected a map header: {}", line));
             }
         } else {
             waiting_lines.push(line);
         }
     }
     if let Some(_name) = current_part {
-        almanac.mappings.push(Mapping::from(waiting_lines.as_ref()));
+        almanac
+            .mappings
+            .push(Mapping::try_from(waiting_lines.as_ref())?);
     }
-    almanac
+    Ok(almanac)
+}
+
+/// The ways in which a map can be suspicious. Line numbers start at 1.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Issue {
+    /// Two lines map the same source values, only the first one is used.
+    OverlappingSources { lines: (usize, usize), overlap: Range },
+    /// Two lines map onto the same destination values. Like `NotBijective`, this only looks at
+    /// the sources a line is actually used for, not the ones an earlier line covers.
+    CollidingDestinations { lines: (usize, usize), overlap: Range },
+    /// The line maps onto values that are also reached by unmapped (identity) source values.
+    NotBijective { line: usize, overlap: Range },
+    /// Source values below the highest mapped one that no line covers, they map to themselves.
+    Gap { range: Range },
+}
+
+impl Issue {
+    /// Gaps are normal in puzzle inputs, everything else is treated as an error in strict mode.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::Gap { .. })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostic {
+    pub section: String,
+    pub issue: Issue,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |r: &Range| format!("[{}, {})", r.start, r.end());
+        write!(f, "{}: ", self.section)?;
+        match &self.issue {
+            Issue::OverlappingSources { lines, overlap } => write!(
+                f,
+                "lines {} and {} have overlapping sources {}",
+                lines.0,
+                lines.1,
+                show(overlap)
+            ),
+            Issue::CollidingDestinations { lines, overlap } => write!(
+                f,
+                "lines {} and {} map onto the same destinations {}",
+                lines.0,
+                lines.1,
+                show(overlap)
+            ),
+            Issue::NotBijective { line, overlap } => write!(
+                f,
+                "line {} maps onto {}, which unmapped values also map to",
+                line,
+                show(overlap)
+            ),
+            Issue::Gap { range } => write!(f, "{} is not mapped", show(range)),
+        }
+    }
+}
+
+/// The parts of `range` that none of `others` cover.
+fn subtract(range: Range, others: &[Range]) -> Vec<Range> {
+    let mut rest = vec![range];
+    for o in others {
+        rest = rest
+            .into_iter()
+            .flat_map(|r| {
+                let Some(overlap) = r.intersection(o) else {
+                    return vec![r];
+                };
+                [
+                    Range::new(r.start, overlap.start - r.start),
+                    Range::new(overlap.end(), r.end() - overlap.end()),
+                ]
+                .into_iter()
+                .filter(|p| p.length > 0)
+                .collect()
+            })
+            .collect();
+    }
+    rest
+}
+
+fn check_section(section: &str, remaps: &[(usize, Remap)]) -> Vec<Diagnostic> {
+    let mut issues = Vec::new();
+
+    for (i, (line1, r1)) in remaps.iter().enumerate() {
+        for (line2, r2) in remaps[(i + 1)..].iter() {
+            let (src1, src2) = (Range::new(r1.src, r1.length), Range::new(r2.src, r2.length));
+            if let Some(overlap) = src1.intersection(&src2) {
+                issues.push(Issue::OverlappingSources {
+                    lines: (*line1, *line2),
+                    overlap,
+                });
+            }
+        }
+    }
+
+    // the destinations that each line actually maps to, as sources covered by an earlier line
+    // use that line instead
+    let mut earlier = Vec::new();
+    let mut destinations: Vec<(usize, Vec<Range>)> = Vec::new();
+    for (line, r) in remaps.iter() {
+        let src = Range::new(r.src, r.length);
+        let dst = subtract(src, &earlier)
+            .into_iter()
+            .map(|p| Range::new(p.start - r.src + r.dst, p.length))
+            .collect();
+        destinations.push((*line, dst));
+        earlier.push(src);
+    }
+
+    for (i, (line1, dst1)) in destinations.iter().enumerate() {
+        for (line2, dst2) in destinations[(i + 1)..].iter() {
+            for d1 in dst1.iter() {
+                for d2 in dst2.iter() {
+                    if let Some(overlap) = d1.intersection(d2) {
+                        issues.push(Issue::CollidingDestinations {
+                            lines: (*line1, *line2),
+                            overlap,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // everything that no line covers maps to itself
+    let sources = merge_ranges(
+        remaps
+            .iter()
+            .filter(|(_, r)| r.length > 0)
+            .map(|(_, r)| Range::new(r.src, r.length))
+            .collect(),
+    );
+    let mut unmapped = Vec::new();
+    let mut next = 0;
+    for s in sources.iter() {
+        if s.start > next {
+            unmapped.push(Range::new(next, s.start - next));
+        }
+        next = s.end();
+    }
+    let gaps = unmapped.clone();
+    unmapped.push(Range::new(next, u64::MAX - next));
+
+    for (line, dst) in destinations.iter() {
+        for d in dst.iter() {
+            for u in unmapped.iter() {
+                if let Some(overlap) = d.intersection(u) {
+                    issues.push(Issue::NotBijective { line: *line, overlap });
+                }
+            }
+        }
+    }
+    issues.extend(gaps.into_iter().map(|range| Issue::Gap { range }));
+
+    issues
+        .into_iter()
+        .map(|issue| Diagnostic {
+            section: section.to_string(),
+            issue,
+        })
+        .collect()
+}
+
+/// Looks for overlaps, collisions and gaps in every map of the almanac.
+pub fn check_almanac(lines: &[&str]) -> Result<Vec<Diagnostic>, String> {
+    let mut res = Vec::new();
+    let mut section: Option<&str> = None;
+    let mut remaps: Vec<(usize, Remap)> = Vec::new();
+
+    for (i, &line) in lines.iter().enumerate() {
+        if let Some(name) = line.strip_suffix(" map:") {
+            section = Some(name);
+        } else if line.is_empty() {
+            if let Some(name) = section.take() {
+                res.extend(check_section(name, &remaps));
+            }
+            remaps.clear();
+        } else if section.is_some() {
+            remaps.push((i + 1, Remap::try_from(line)?));
+        }
+    }
+    if let Some(name) = section {
+        res.extend(check_section(name, &remaps));
+    }
+
+    Ok(res)
+}
+
+/// Like `parse_almanac`, but fails if any map has overlapping sources or isn't a bijection.
+pub fn parse_almanac_strict(lines: &[&str], seeds_as_ranges: bool) -> Result<Almanac, String> {
+    let almanac = parse_almanac(lines, seeds_as_ranges)?;
+    let errors: Vec<String> = check_almanac(lines)?
+        .iter()
+        .filter(|d| d.issue.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(almanac)
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if !lines.first().is_some_and(|l| l.starts_with("seeds: ")) {
+        return Err("missing 'seeds: ' header".to_string());
+    }
+    let almanac = parse_almanac(lines, false)?;
+    if almanac.mappings.is_empty() {
+        return Err("no maps found".to_string());
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
-    let almanac = parse_almanac(lines.as_ref(), !part1);
+    let almanac = parse_almanac(lines.as_ref(), !part1).unwrap();
     almanac
         .locations()
         .into_iter()
@@ -221,6 +652,30 @@ pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
         .unwrap()
 }
 
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let almanac = parse_almanac(&lines, true).unwrap();
+    let mut stages = String::new();
+    let mut current = almanac.seeds.clone();
+    stages += &format!("{:?}\n", current);
+    for mapping in almanac.mappings.iter() {
+        current = mapping.apply_ranges(&current);
+        stages += &format!("{:?}\n", current);
+    }
+    vec![
+        ("almanac", format!("{:#?}\n", almanac)),
+        ("range_stages", stages),
+        ("composed", almanac.composed().to_string()),
+        (
+            "diagnostics",
+            check_almanac(&lines)
+                .unwrap()
+                .iter()
+                .map(|d| format!("{}\n", d))
+                .collect(),
+        ),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use crate::d5::*;
@@ -228,7 +683,7 @@ mod tests {
     #[test]
     fn remap_from_line() {
         assert_eq!(
-            Remap::from("37 52 2"),
+            Remap::try_from("37 52 2").unwrap(),
             Remap {
                 dst: 37,
                 src: 52,
@@ -253,7 +708,8 @@ mod tests {
                 ]
                 .as_ref(),
                 false
-            ),
+            )
+            .unwrap(),
             Almanac {
                 seeds: vec![Range::new(1, 1), Range::new(2, 1)],
                 mappings: vec![
@@ -271,7 +727,7 @@ mod tests {
     #[test]
     fn almanac_from_lines_with_seeds_as_ranges() {
         assert_eq!(
-            parse_almanac(["seeds: 1 2 13 14"].as_ref(), true),
+            parse_almanac(["seeds: 1 2 13 14"].as_ref(), true).unwrap(),
             Almanac {
                 seeds: vec![Range::new(1, 2), Range::new(13, 14)],
                 mappings: vec![]
@@ -1,66 +1,183 @@
-fn calc_distance(race_time: u64, charge_time: u64) -> u64 {
-    let speed = charge_time;
-    let travel_time = race_time - charge_time;
-    travel_time * speed
+/// How a boat moves: every millisecond of charging adds `acceleration` to its speed, up to
+/// `speed_cap`, and after letting go it waits `penalty` milliseconds before it starts moving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Motion {
+    pub acceleration: u64,
+    pub speed_cap: Option<u64>,
+    pub penalty: u64,
 }
 
-fn calc_good_choices(race_time: u64, record_distance: u64) -> u64 {
-    let mut good_choices = 0;
-    for charge_time in 1..race_time {
-        let distance = calc_distance(race_time, charge_time);
-        if distance > record_distance {
-            good_choices += 1;
+impl Default for Motion {
+    /// The boats of the puzzle.
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            speed_cap: None,
+            penalty: 0,
         }
     }
-    good_choices
 }
 
+impl Motion {
+    pub fn distance(&self, race_time: u64, charge_time: u64) -> u128 {
+        let speed = self.acceleration as u128 * charge_time as u128;
+        let speed = self.speed_cap.map_or(speed, |cap| speed.min(cap as u128));
+        let travel_time = race_time.saturating_sub(charge_time).saturating_sub(self.penalty);
+        speed * travel_time as u128
+    }
+
+    /// Tries every charge time, only useful for checking `good_choices`.
+    pub fn good_choices_simulated(&self, race_time: u64, record_distance: u64) -> u64 {
+        (0..=race_time)
+            .filter(|&c| self.distance(race_time, c) > record_distance as u128)
+            .count() as u64
+    }
+
+    /// The number of charge times that beat the record, in constant time.
+    pub fn good_choices(&self, race_time: u64, record_distance: u64) -> Result<u64, String> {
+        // charging longer than this leaves no time to move
+        let Some(t) = race_time.checked_sub(self.penalty) else {
+            return Ok(0);
+        };
+        let a = self.acceleration as u128;
+        let (t, r) = (t as u128, record_distance as u128);
+
+        // from this charge time on the speed is capped and the distance falls linearly
+        let capped_from = match self.speed_cap {
+            Some(_) if a == 0 => t + 1,
+            Some(cap) => (cap as u128).div_ceil(a).min(t + 1),
+            None => t + 1,
+        };
+
+        let mut count = 0;
+        if let Some((lo, hi)) = quadratic_solutions(a, t, r)? {
+            let hi = hi.min(capped_from.saturating_sub(1));
+            if capped_from > 0 && lo <= hi {
+                count += hi - lo + 1;
+            }
+        }
+        if let Some(cap) = self.speed_cap
+            && cap > 0
+        {
+            // cap * (t - c) > r  <=>  c <= t - r / cap - 1
+            if let Some(last) = t.checked_sub(r / cap as u128 + 1)
+                && last >= capped_from
+            {
+                count += last - capped_from + 1;
+            }
+        }
+
+        Ok(count as u64)
+    }
+}
+
+/// The charge times `lo..=hi` in `0..=t` for which `a * c * (t - c) > r`, if there are any.
+/// The roots come from an integer square root, followed by exact checks of the neighbours.
+fn quadratic_solutions(a: u128, t: u128, r: u128) -> Result<Option<(u128, u128)>, String> {
+    let too_big = || "numbers are too big".to_string();
+    let f = |c: u128| -> Result<u128, String> {
+        a.checked_mul(c)
+            .and_then(|v| v.checked_mul(t - c))
+            .ok_or_else(too_big)
+    };
+
+    // the distance is symmetric around t / 2, where it is the largest
+    if f(t / 2)? <= r {
+        return Ok(None);
+    }
+
+    // a * c^2 - a * t * c + r = 0  =>  c = (a * t - sqrt(a^2 * t^2 - 4 * a * r)) / 2a
+    let at = a.checked_mul(t).ok_or_else(too_big)?;
+    let disc = at
+        .checked_mul(at)
+        .and_then(|v| v.checked_sub(a.checked_mul(r)?.checked_mul(4)?))
+        .ok_or_else(too_big)?;
+    let mut lo = (at - disc.isqrt()) / (2 * a);
+
+    while lo > 0 && f(lo - 1)? > r {
+        lo -= 1;
+    }
+    while f(lo)? <= r {
+        lo += 1;
+    }
+
+    Ok(Some((lo, t - lo)))
+}
+
+#[derive(Debug)]
 struct Input {
     race_times: Vec<u64>,
     record_distances: Vec<u64>,
 }
 
-fn parse_line(prefix: &str, line: &str, use_kerning: bool) -> Vec<u64> {
+fn parse_line(prefix: &str, line: &str, use_kerning: bool) -> Result<Vec<u64>, String> {
+    let line = line
+        .strip_prefix(prefix)
+        .ok_or_else(|| format!("expected line starting with '{}'", prefix))?;
+
     if use_kerning {
-        vec![line
-            .strip_prefix(prefix)
-            .unwrap()
+        let number = line
             .chars()
             .filter(|c| !c.is_whitespace())
             .collect::<String>()
             .parse::<u64>()
-            .unwrap()]
+            .map_err(|e| format!("invalid number: {}", e))?;
+        Ok(vec![number])
     } else {
-        line.strip_prefix(prefix)
-            .unwrap()
-            .split(' ')
+        line.split(' ')
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<u64>().unwrap())
+            .map(|s| s.parse::<u64>().map_err(|e| format!("invalid number {}: {}", s, e)))
             .collect()
     }
 }
 
-fn parse_input(lines: Vec<&str>, use_kerning: bool) -> Input {
-    let race_times: Vec<u64> = parse_line("Time:", lines[0], use_kerning);
-    let record_distances: Vec<u64> = parse_line("Distance:", lines[1], use_kerning);
+fn parse_input(lines: &[&str], use_kerning: bool) -> Result<Input, String> {
+    let [time_line, distance_line] = lines[..] else {
+        return Err("expected exactly two lines".to_string());
+    };
+    let race_times: Vec<u64> = parse_line("Time:", time_line, use_kerning)?;
+    let record_distances: Vec<u64> = parse_line("Distance:", distance_line, use_kerning)?;
+
+    if race_times.len() != record_distances.len() {
+        return Err("different number of times and distances".to_string());
+    }
 
-    Input {
+    Ok(Input {
         race_times,
         record_distances,
-    }
+    })
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    parse_input(lines, false).map(|_| ())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
     let Input {
         race_times,
         record_distances,
-    } = parse_input(lines, !part1);
-
-    assert_eq!(race_times.len(), record_distances.len());
+    } = parse_input(&lines, !part1).unwrap();
 
+    let motion = Motion::default();
     let mut res = 1;
     for race_id in 0..race_times.len() {
-        res *= calc_good_choices(race_times[race_id], record_distances[race_id]);
+        res *= motion
+            .good_choices(race_times[race_id], record_distances[race_id])
+            .unwrap();
+    }
+    res
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut res = Vec::new();
+    for (name, use_kerning) in [("races", false), ("races_kerned", true)] {
+        let input = parse_input(&lines, use_kerning).unwrap();
+        let mut text = format!("{:?}\n", input);
+        for (&t, &d) in input.race_times.iter().zip(input.record_distances.iter()) {
+            let good = Motion::default().good_choices(t, d).unwrap();
+            text += &format!("time {} record {}: {} good choices\n", t, d, good);
+        }
+        res.push((name, text));
     }
     res
 }
@@ -1,167 +1,581 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::collections::HashMap;
 
-fn parse_card(c: char, use_jokers: bool) -> Result<u8, String> {
-    match c {
-        'A' => Ok(14),
-        'K' => Ok(13),
-        'Q' => Ok(12),
-        'J' => Ok(if use_jokers { 1 } else { 11 }),
-        'T' => Ok(10),
-        '2'..='9' => Ok((c as u8) - b'0'),
-        _ => Err("invalid card".to_string()),
-    }
+/// A card as its position in the card order of the rules (0 is the weakest).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Card {
+    pub rank: u8,
+    pub suit: Option<char>,
+    pub wild: bool,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Cards([u8; 5]);
-
-impl Cards {
-    fn hand_type(&self) -> HandType {
-        for i in 0..5 {
-            if self.0[i] == 1 {
-                let mut card = *self;
-                let mut best = HandType::HighCard;
-                for n in 2..15 {
-                    card.0[i] = n;
-                    let t = card.hand_type();
-                    if t > best {
-                        best = t;
-                    }
-                }
-                return best;
-            }
-        }
-        let mut counters: HashMap<u8, u8> = HashMap::new();
-        for n in self.0 {
-            counters.insert(n, counters.get(&n).unwrap_or(&0) + 1);
-        }
-        let c: Vec<u8> = counters.into_values().collect();
-
-        if c.contains(&5) {
-            HandType::FiveOfAKind
-        } else if c.contains(&4) {
-            HandType::FourOfAKind
-        } else if c.contains(&3) {
-            if c.contains(&2) {
-                HandType::FullHouse
-            } else {
-                HandType::ThreeOfAKind
-            }
-        } else if c.iter().filter(|&n| *n == 2).count() == 2 {
-            HandType::TwoPairs
-        } else if c.contains(&2) {
-            HandType::OnePair
-        } else {
-            HandType::HighCard
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Category {
     HighCard,
     OnePair,
     TwoPairs,
     ThreeOfAKind,
+    /// As many consecutive ranks as there are cards, the strongest card may also come before
+    /// the weakest one (A2345 in poker).
+    Straight,
+    /// All cards of the same suit.
+    Flush,
     FullHouse,
     FourOfAKind,
+    StraightFlush,
     FiveOfAKind,
 }
 
-struct Hand {
-    cards: Cards,
-    bid: u64,
+/// How hands of the same category are ordered.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TieBreak {
+    /// Card by card, in the order they were dealt.
+    CardOrder,
+    /// The ranks that appear most often first, then the higher ranks, ignoring wild cards.
+    /// Straights only compare their highest card.
+    Poker,
 }
 
-impl PartialOrd for Cards {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub hand_size: usize,
+    /// All ranks from weakest to strongest, including the wild cards.
+    pub card_order: Vec<char>,
+    /// Wild cards take whatever rank and suit give the best category.
+    pub wildcards: Vec<char>,
+    /// If set, every card is written as its rank followed by its suit, e.g. `TH`.
+    pub suits: bool,
+    /// The categories that count, from weakest to strongest.
+    pub categories: Vec<Category>,
+    pub tie_break: TieBreak,
 }
 
-impl Ord for Cards {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.hand_type().cmp(&other.hand_type()).then_with(|| {
-            let mut r = Ordering::Equal;
-            for i in 0..5 {
-                r = self.0[i].cmp(&other.0[i]);
-                if r != Ordering::Equal {
-                    break;
-                }
+/// Sorts like the hand it was made from.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct HandKey {
+    category: usize,
+    tie_break: Vec<u8>,
+}
+
+impl Rules {
+    /// Part 1 of the puzzle.
+    pub fn camel_cards() -> Self {
+        use Category::*;
+
+        Self {
+            hand_size: 5,
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            suits: false,
+            categories: vec![
+                HighCard,
+                OnePair,
+                TwoPairs,
+                ThreeOfAKind,
+                FullHouse,
+                FourOfAKind,
+                FiveOfAKind,
+            ],
+            tie_break: TieBreak::CardOrder,
+        }
+    }
+
+    /// Part 2 of the puzzle, where J is a joker and the weakest card.
+    pub fn camel_cards_with_jokers() -> Self {
+        Self {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Self::camel_cards()
+        }
+    }
+
+    /// Five card poker without wild cards.
+    pub fn poker() -> Self {
+        use Category::*;
+
+        Self {
+            hand_size: 5,
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            suits: true,
+            categories: vec![
+                HighCard,
+                OnePair,
+                TwoPairs,
+                ThreeOfAKind,
+                Straight,
+                Flush,
+                FullHouse,
+                FourOfAKind,
+                StraightFlush,
+            ],
+            tie_break: TieBreak::Poker,
+        }
+    }
+
+    /// Whitespace between the cards is ignored.
+    pub fn parse_cards(&self, s: &str) -> Result<Vec<Card>, String> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let per_card = if self.suits { 2 } else { 1 };
+        if chars.len() != self.hand_size * per_card {
+            return Err(format!("expected {} cards: {}", self.hand_size, s));
+        }
+
+        chars
+            .chunks(per_card)
+            .map(|c| {
+                let rank = self
+                    .card_order
+                    .iter()
+                    .position(|&r| r == c[0])
+                    .ok_or_else(|| format!("invalid card: {}", c[0]))?;
+                Ok(Card {
+                    rank: rank as u8,
+                    suit: c.get(1).copied(),
+                    wild: self.wildcards.contains(&c[0]),
+                })
+            })
+            .collect()
+    }
+
+    /// The strongest category of the rules that the hand can form.
+    pub fn category(&self, cards: &[Card]) -> Category {
+        self.best_category(cards).0
+    }
+
+    /// Also returns the highest card of the straight, if the category is one.
+    fn best_category(&self, cards: &[Card]) -> (Category, Option<u8>) {
+        use Category::*;
+
+        let wild = cards.iter().filter(|c| c.wild).count();
+        let mut counts: Vec<usize> = cards
+            .iter()
+            .filter(|c| !c.wild)
+            .fold(HashMap::new(), |mut m: HashMap<u8, usize>, c| {
+                *m.entry(c.rank).or_default() += 1;
+                m
+            })
+            .into_values()
+            .collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        let c1 = counts.first().copied().unwrap_or(0);
+        let c2 = counts.get(1).copied().unwrap_or(0);
+
+        // wild cards needed to turn the two largest groups into groups of `a` and `b`
+        let missing = |a: usize, b: usize| a.saturating_sub(c1) + b.saturating_sub(c2);
+
+        let straight = self.straight_high(cards);
+        let flush = self.hand_size >= 5 && {
+            let mut suits = cards.iter().filter(|c| !c.wild).map(|c| c.suit);
+            let first = suits.next();
+            first.is_none_or(|s| s.is_some() && suits.all(|t| t == s))
+        };
+
+        let possible = |category: Category| match category {
+            HighCard => true,
+            OnePair => missing(2, 0) <= wild,
+            TwoPairs => missing(2, 2) <= wild,
+            ThreeOfAKind => missing(3, 0) <= wild,
+            Straight => straight.is_some(),
+            Flush => flush,
+            FullHouse => missing(3, 2) <= wild,
+            FourOfAKind => missing(4, 0) <= wild,
+            StraightFlush => straight.is_some() && flush,
+            FiveOfAKind => missing(5, 0) <= wild,
+        };
+
+        let best = self
+            .categories
+            .iter()
+            .rev()
+            .copied()
+            .find(|&c| possible(c))
+            .unwrap_or(HighCard);
+        let is_straight = best == Straight || best == StraightFlush;
+        (best, straight.filter(|_| is_straight))
+    }
+
+    /// The highest card of the best straight the hand can form, if any.
+    fn straight_high(&self, cards: &[Card]) -> Option<u8> {
+        let len = self.hand_size;
+        let num_ranks = self.card_order.len();
+        if len < 2 || num_ranks < len {
+            return None;
+        }
+        let mut ranks: Vec<u8> = cards.iter().filter(|c| !c.wild).map(|c| c.rank).collect();
+        ranks.sort();
+        if ranks.windows(2).any(|w| w[0] == w[1]) {
+            return None;
+        }
+
+        (len - 2..num_ranks)
+            .rev()
+            .map(|h| h as u8)
+            .find(|&h| {
+                let window = self.straight_window(h);
+                ranks.iter().all(|r| window.contains(r))
+                    && !window.iter().any(|&r| self.is_wild_rank(r))
+            })
+    }
+
+    /// The ranks of the straight with the highest card `high`, where the strongest card may
+    /// wrap around to below the weakest one, but no further.
+    fn straight_window(&self, high: u8) -> Vec<u8> {
+        let top = (self.card_order.len() - 1) as u8;
+        let low = high as isize + 1 - self.hand_size as isize;
+        (low..=high as isize)
+            .map(|r| if r < 0 { top } else { r as u8 })
+            .collect()
+    }
+
+    fn is_wild_rank(&self, rank: u8) -> bool {
+        self.wildcards.contains(&self.card_order[rank as usize])
+    }
+
+    /// The hand with every wild card replaced by the card it stands for in the best category.
+    pub fn substitute(&self, cards: &[Card]) -> Vec<Card> {
+        use Category::*;
+
+        let (category, straight_high) = self.best_category(cards);
+        let fixed: Vec<&Card> = cards.iter().filter(|c| !c.wild).collect();
+        // wild cards keep their written suit, unless they complete a flush
+        let flush = category == Flush || category == StraightFlush;
+        let flush_suit = fixed.first().map_or(cards[0].suit, |c| c.suit);
+
+        // the ranks the wild cards become, in order
+        let mut targets: Vec<u8> = if let Some(high) = straight_high {
+            self.straight_window(high)
+                .into_iter()
+                .filter(|r| !fixed.iter().any(|c| c.rank == *r))
+                .collect()
+        } else {
+            let mut groups: Vec<(usize, u8)> = fixed
+                .iter()
+                .fold(HashMap::new(), |mut m: HashMap<u8, usize>, c| {
+                    *m.entry(c.rank).or_default() += 1;
+                    m
+                })
+                .into_iter()
+                .map(|(rank, count)| (count, rank))
+                .collect();
+            groups.sort_by(|a, b| b.cmp(a));
+
+            let (a, b): (usize, usize) = match category {
+                FiveOfAKind => (5, 0),
+                FourOfAKind => (4, 0),
+                FullHouse => (3, 2),
+                ThreeOfAKind => (3, 0),
+                TwoPairs => (2, 2),
+                OnePair => (2, 0),
+                _ => (0, 0),
+            };
+            let mut targets = Vec::new();
+            for (&(count, rank), want) in groups.iter().zip([a, b]) {
+                targets.extend(std::iter::repeat_n(rank, want.saturating_sub(count)));
             }
-            r
-        })
+            // any others join the largest group, or become the strongest missing ranks
+            let strongest: Vec<u8> = (0..self.card_order.len() as u8)
+                .rev()
+                .filter(|&r| !self.is_wild_rank(r))
+                .collect();
+            if a > 0 {
+                let rank = groups.first().map_or(strongest[0], |&(_, rank)| rank);
+                targets.extend(std::iter::repeat_n(rank, cards.len()));
+            }
+            targets.extend(
+                strongest
+                    .into_iter()
+                    .filter(|r| !fixed.iter().any(|c| c.rank == *r)),
+            );
+            targets
+        };
+
+        targets.reverse();
+        cards
+            .iter()
+            .map(|c| {
+                if c.wild {
+                    Card {
+                        rank: targets.pop().unwrap_or(c.rank),
+                        suit: if flush { flush_suit } else { c.suit },
+                        wild: false,
+                    }
+                } else {
+                    *c
+                }
+            })
+            .collect()
+    }
+
+    pub fn format_cards(&self, cards: &[Card]) -> String {
+        cards
+            .iter()
+            .flat_map(|c| [Some(self.card_order[c.rank as usize]), c.suit])
+            .flatten()
+            .collect()
+    }
+
+    pub fn key(&self, cards: &[Card]) -> HandKey {
+        let (category, straight_high) = self.best_category(cards);
+        let category = self
+            .categories
+            .iter()
+            .position(|&c| c == category)
+            .unwrap_or(0);
+
+        let tie_break = match self.tie_break {
+            TieBreak::CardOrder => cards.iter().map(|c| c.rank).collect(),
+            TieBreak::Poker => match straight_high {
+                Some(high) => vec![high],
+                None => {
+                    let mut groups: Vec<(usize, u8)> = cards
+                        .iter()
+                        .filter(|c| !c.wild)
+                        .fold(HashMap::new(), |mut m: HashMap<u8, usize>, c| {
+                            *m.entry(c.rank).or_default() += 1;
+                            m
+                        })
+                        .into_iter()
+                        .map(|(rank, count)| (count, rank))
+                        .collect();
+                    groups.sort_by(|a, b| b.cmp(a));
+                    groups.into_iter().map(|(_, rank)| rank).collect()
+                }
+            },
+        };
+
+        HandKey {
+            category,
+            tie_break,
+        }
     }
 }
 
-pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
-    let mut hands = Vec::new();
+struct Hand<'a> {
+    cards_str: &'a str,
+    cards: Vec<Card>,
+    key: HandKey,
+    bid: u64,
+}
+
+fn parse_hand<'a>(line: &'a str, rules: &Rules) -> Result<Hand<'a>, String> {
+    let (cards_str, bid_str) = line.trim_end().rsplit_once(' ').ok_or("missing bid")?;
+    let cards = rules.parse_cards(cards_str)?;
+    let bid = bid_str
+        .parse::<u64>()
+        .map_err(|e| format!("invalid bid: {}", e))?;
+    Ok(Hand {
+        cards_str,
+        key: rules.key(&cards),
+        cards,
+        bid,
+    })
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no hands".to_string());
+    }
     for line in lines {
-        let mut cards = [0; 5];
-        for (i, c) in line.chars().take(5).enumerate() {
-            let card = parse_card(c, !part1).unwrap();
-            cards[i] = card;
-        }
-        let bid = line[6..].parse::<u64>().unwrap();
-        hands.push(Hand {
-            cards: Cards(cards),
-            bid,
-        });
-    }
-    hands.sort_by_key(|h| h.cards);
-    hands
+        parse_hand(line, &Rules::camel_cards())?;
+    }
+    Ok(())
+}
+
+fn ranked_hands<'a>(lines: &[&'a str], rules: &Rules) -> Result<Vec<Hand<'a>>, String> {
+    let mut hands = lines
+        .iter()
+        .map(|line| parse_hand(line, rules))
+        .collect::<Result<Vec<_>, _>>()?;
+    hands.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(hands)
+}
+
+/// The total winnings: the bid of every hand times its rank, the weakest hand having rank 1.
+pub fn run_with_rules(lines: &[&str], rules: &Rules) -> Result<u64, String> {
+    Ok(ranked_hands(lines, rules)?
         .iter()
         .enumerate()
         .map(|(i, h)| (i + 1) as u64 * h.bid)
-        .sum()
+        .sum())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Explanation {
+    pub hand: String,
+    /// The hand with the wild cards replaced.
+    pub substituted: String,
+    pub category: Category,
+    /// 1 for the weakest hand.
+    pub rank: usize,
+    pub bid: u64,
+}
+
+/// How every hand (in input order) was valued and ranked.
+pub fn explain(lines: &[&str], rules: &Rules) -> Result<Vec<Explanation>, String> {
+    let hands = lines
+        .iter()
+        .map(|line| parse_hand(line, rules))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // same order as `ranked_hands`, which sorts stably
+    let mut order: Vec<usize> = (0..hands.len()).collect();
+    order.sort_by(|&a, &b| hands[a].key.cmp(&hands[b].key));
+    let mut ranks = vec![0; hands.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank + 1;
+    }
+
+    Ok(hands
+        .iter()
+        .zip(ranks)
+        .map(|(h, rank)| Explanation {
+            hand: h.cards_str.to_string(),
+            substituted: rules.format_cards(&rules.substitute(&h.cards)),
+            category: rules.category(&h.cards),
+            rank,
+            bid: h.bid,
+        })
+        .collect())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RankChange {
+    pub hand: String,
+    pub bid: u64,
+    pub rank_before: usize,
+    pub rank_after: usize,
+}
+
+impl RankChange {
+    /// How much the winnings of this hand changed.
+    pub fn winnings_change(&self) -> i64 {
+        (self.rank_after as i64 - self.rank_before as i64) * self.bid as i64
+    }
+}
+
+/// The hands (in input order) whose rank differs between the two rule sets.
+pub fn rank_changes(
+    lines: &[&str],
+    before: &Rules,
+    after: &Rules,
+) -> Result<Vec<RankChange>, String> {
+    let before = explain(lines, before)?;
+    let after = explain(lines, after)?;
+
+    Ok(before
+        .into_iter()
+        .zip(after)
+        .filter(|(b, a)| b.rank != a.rank)
+        .map(|(b, a)| RankChange {
+            hand: b.hand,
+            bid: b.bid,
+            rank_before: b.rank,
+            rank_after: a.rank,
+        })
+        .collect())
+}
+
+pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
+    let rules = if part1 {
+        Rules::camel_cards()
+    } else {
+        Rules::camel_cards_with_jokers()
+    };
+    run_with_rules(&lines, &rules).unwrap()
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut res = Vec::new();
+    for (name, rules) in [
+        ("ranking", Rules::camel_cards()),
+        ("ranking_jokers", Rules::camel_cards_with_jokers()),
+    ] {
+        let text = ranked_hands(&lines, &rules)
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                let t = rules.categories[h.key.category];
+                format!("{}: {} {:?} bid {}\n", i + 1, h.cards_str, t, h.bid)
+            })
+            .collect();
+        res.push((name, text));
+    }
+
+    let explained = explain(&lines, &Rules::camel_cards_with_jokers())
+        .unwrap()
+        .iter()
+        .map(|e| {
+            format!(
+                "{} as {} {:?} rank {}\n",
+                e.hand, e.substituted, e.category, e.rank
+            )
+        })
+        .collect();
+    res.push(("jokers_explained", explained));
+
+    let changes = rank_changes(&lines, &Rules::camel_cards(), &Rules::camel_cards_with_jokers())
+        .unwrap()
+        .iter()
+        .map(|c| {
+            format!(
+                "{} bid {}: rank {} -> {} ({:+})\n",
+                c.hand,
+                c.bid,
+                c.rank_before,
+                c.rank_after,
+                c.winnings_change()
+            )
+        })
+        .collect();
+    res.push(("rank_changes", changes));
+
+    res
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn category(rules: &Rules, cards: &str) -> Category {
+        rules.category(&rules.parse_cards(cards).unwrap())
+    }
+
     #[test]
     fn type_hands() {
-        assert_eq!(Cards([11, 11, 11, 11, 11]).hand_type(), HandType::FiveOfAKind);
-        assert_eq!(Cards([11, 11, 11, 11, 12]).hand_type(), HandType::FourOfAKind);
-        assert_eq!(Cards([11, 11, 11, 12, 12]).hand_type(), HandType::FullHouse);
-        assert_eq!(Cards([11, 11, 11, 12, 13]).hand_type(), HandType::ThreeOfAKind);
-        assert_eq!(Cards([11, 11, 12, 12, 13]).hand_type(), HandType::TwoPairs);
-        assert_eq!(Cards([11, 11, 12, 13, 14]).hand_type(), HandType::OnePair);
-        assert_eq!(Cards([11, 12, 13, 14, 15]).hand_type(), HandType::HighCard);
+        use Category::*;
+
+        let rules = Rules::camel_cards();
+        assert_eq!(category(&rules, "JJJJJ"), FiveOfAKind);
+        assert_eq!(category(&rules, "JJJJQ"), FourOfAKind);
+        assert_eq!(category(&rules, "JJJQQ"), FullHouse);
+        assert_eq!(category(&rules, "JJJQK"), ThreeOfAKind);
+        assert_eq!(category(&rules, "JJQQK"), TwoPairs);
+        assert_eq!(category(&rules, "JJQKA"), OnePair);
+        assert_eq!(category(&rules, "TJQKA"), HighCard); // no straights in camel cards
     }
 
     #[test]
     fn type_hands_with_jokes() {
-        assert_eq!(Cards([1, 11, 11, 11, 11]).hand_type(), HandType::FiveOfAKind);
-        assert_eq!(Cards([1, 11, 11, 11, 12]).hand_type(), HandType::FourOfAKind);
-        assert_eq!(Cards([1, 11, 11, 12, 12]).hand_type(), HandType::FullHouse);
-        assert_eq!(Cards([1, 11, 11, 12, 13]).hand_type(), HandType::ThreeOfAKind);
-        assert_eq!(Cards([1, 11, 12, 12, 13]).hand_type(), HandType::ThreeOfAKind); // !!
-        assert_eq!(Cards([1, 11, 12, 13, 14]).hand_type(), HandType::OnePair);
-        assert_eq!(Cards([1, 12, 13, 14, 15]).hand_type(), HandType::OnePair); // !!
+        use Category::*;
+
+        let rules = Rules::camel_cards_with_jokers();
+        assert_eq!(category(&rules, "JJJJJ"), FiveOfAKind);
+        assert_eq!(category(&rules, "JTTTT"), FiveOfAKind);
+        assert_eq!(category(&rules, "JTTTQ"), FourOfAKind);
+        assert_eq!(category(&rules, "JTTQQ"), FullHouse);
+        assert_eq!(category(&rules, "JTTQK"), ThreeOfAKind);
+        assert_eq!(category(&rules, "JTQKA"), OnePair);
     }
 
     #[test]
     fn sort_hands() {
-        let mut cards = [
-            Cards([11, 11, 11, 11, 11]),
-            Cards([11, 11, 11, 11, 12]),
-            Cards([11, 11, 11, 12, 12]),
-            Cards([11, 11, 11, 12, 13]),
-            Cards([11, 11, 12, 12, 13]),
-            Cards([11, 11, 12, 13, 14]),
-            Cards([11, 12, 13, 14, 10]),
-        ];
-        cards.sort();
-        assert_eq!(cards, [
-            Cards([11, 12, 13, 14, 10]),
-            Cards([11, 11, 12, 13, 14]),
-            Cards([11, 11, 12, 12, 13]),
-            Cards([11, 11, 11, 12, 13]),
-            Cards([11, 11, 11, 12, 12]),
-            Cards([11, 11, 11, 11, 12]),
-            Cards([11, 11, 11, 11, 11]),
-        ]);
+        let rules = Rules::camel_cards();
+        let mut hands = ["JJJJJ", "JJJJQ", "JJJQQ", "JJJQK", "JJQQK", "JJQKA", "JQKAT"];
+        hands.sort_by_cached_key(|h| rules.key(&rules.parse_cards(h).unwrap()));
+        assert_eq!(
+            hands,
+            ["JQKAT", "JJQKA", "JJQQK", "JJJQK", "JJJQQ", "JJJJQ", "JJJJJ"]
+        );
     }
 }
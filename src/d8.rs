@@ -1,106 +1,554 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
 
 use gcd::Gcd;
 
+/// The steps at which a walker is on a goal node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// Steps before `start`, which only happen once.
+    pub transient: Vec<u64>,
+    /// Steps in `start..start + length`, which repeat every `length` steps.
+    pub offsets: Vec<u64>,
+    pub start: u64,
+    pub length: u64,
+}
+
+impl Cycle {
+    /// Offsets before `start` and cycles of length 0 never match a step after `start`.
+    pub fn contains(&self, n: u64) -> bool {
+        if n < self.start {
+            return self.transient.contains(&n);
+        }
+        if self.length == 0 {
+            return false;
+        }
+        let phase = (n - self.start) % self.length;
+        self.offsets
+            .iter()
+            .any(|&o| o.checked_sub(self.start) == Some(phase))
+    }
+
+    /// The first step, or `None` if there is none.
+    pub fn first(&self) -> Option<u64> {
+        self.transient
+            .iter()
+            .chain(self.offsets.iter())
+            .copied()
+            .min()
+    }
+
+    /// The steps that are in both cycles, as a cycle whose length is the lcm of the lengths.
+    pub fn intersect(&self, other: &Cycle) -> Result<Cycle, String> {
+        let overflow = || "the combined cycle is too long".to_string();
+        if self.length == 0 || other.length == 0 {
+            return Err("cycle of length 0".to_string());
+        }
+
+        let mut transient: Vec<u64> = self
+            .transient
+            .iter()
+            .filter(|&&n| other.contains(n))
+            .chain(other.transient.iter().filter(|&&n| self.contains(n)))
+            .copied()
+            .collect();
+
+        let start = self.start.max(other.start);
+        let mut offsets = Vec::new();
+        let mut length = None;
+        for &a in self.offsets.iter() {
+            for &b in other.offsets.iter() {
+                let Some((x, lcm)) = crt(a, self.length, b, other.length) else {
+                    continue;
+                };
+                length = Some(lcm);
+                // the first solution at or after the start of both cycles
+                let first = start as u128 + (x + lcm - start as u128 % lcm) % lcm;
+                offsets.push(u64::try_from(first).map_err(|_| overflow())?);
+            }
+        }
+        let length = match length {
+            Some(l) => u64::try_from(l).map_err(|_| overflow())?,
+            None => (self.length as u128 * other.length as u128 / gcd(self.length, other.length))
+                .try_into()
+                .unwrap_or(1), // no repeating steps, so the length doesn't matter
+        };
+
+        // steps of the repeating part before `start` only happen once
+        for (c, d) in [(self, other), (other, self)] {
+            for &o in c.offsets.iter().filter(|&&o| o < start) {
+                transient.extend(
+                    (o..start)
+                        .step_by(c.length as usize)
+                        .filter(|&n| d.contains(n)),
+                );
+            }
+        }
+        transient.sort();
+        transient.dedup();
+        offsets.sort();
+        offsets.dedup();
+
+        Ok(Cycle {
+            transient,
+            offsets,
+            start,
+            length,
+        })
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u128 {
+    a.gcd_binary(b) as u128
+}
+
+/// `a * b % m` without overflowing.
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(p) = a.checked_mul(b) {
+        return p % m;
+    }
+    let (mut a, mut b, mut res) = (a % m, b, 0u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            res = (res + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    res
+}
+
+/// The inverse of `a` modulo `m`, which must be coprime.
+fn inverse_mod(a: u128, m: u128) -> u128 {
+    let (mut old_r, mut r) = (a as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    old_s.rem_euclid(m as i128) as u128
+}
+
+/// Solves `x = a1 (mod m1)` and `x = a2 (mod m2)`, also for moduli that are not coprime.
+/// Returns `x` modulo the lcm of the moduli and the lcm, or `None` if there is no solution.
+pub fn crt(a1: u64, m1: u64, a2: u64, m2: u64) -> Option<(u128, u128)> {
+    let g = gcd(m1, m2);
+    let (m1, m2) = (m1 as u128, m2 as u128);
+    let (a1, a2) = (a1 as u128 % m1, a2 as u128 % m2);
+
+    let diff = (a2 + m2 - a1 % m2) % m2;
+    if diff % g != 0 {
+        return None;
+    }
+    let (m1g, m2g) = (m1 / g, m2 / g);
+    // a1 + m1 * t = a2 (mod m2)  <=>  (m1 / g) * t = diff / g (mod m2 / g)
+    let t = if m2g == 1 {
+        0
+    } else {
+        mul_mod(diff / g % m2g, inverse_mod(m1g % m2g, m2g), m2g)
+    };
+    let lcm = m1g * m2;
+    Some(((a1 + m1 * t) % lcm, lcm))
+}
+
+/// A set of nodes, for choosing where walkers start and where they are going.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeSet {
+    /// Nodes whose name ends with the given suffix.
+    Suffix(String),
+    /// Nodes whose name matches the pattern, where '?' matches any character and '*' any
+    /// number of characters.
+    Pattern(String),
+    /// Exactly the given nodes.
+    Nodes(Vec<String>),
+}
+
+impl NodeSet {
+    pub fn node(name: &str) -> NodeSet {
+        NodeSet::Nodes(vec![name.to_string()])
+    }
+
+    pub fn contains(&self, node: &str) -> bool {
+        match self {
+            NodeSet::Suffix(suffix) => node.ends_with(suffix.as_str()),
+            NodeSet::Pattern(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                let node: Vec<char> = node.chars().collect();
+                glob_match(&pattern, &node)
+            }
+            NodeSet::Nodes(nodes) => nodes.iter().any(|n| n == node),
+        }
+    }
+}
+
+fn glob_match(pattern: &[char], s: &[char]) -> bool {
+    match pattern.split_first() {
+        None => s.is_empty(),
+        Some(('*', rest)) => (0..=s.len()).any(|i| glob_match(rest, &s[i..])),
+        Some((&p, rest)) => s
+            .split_first()
+            .is_some_and(|(&c, s)| (p == '?' || p == c) && glob_match(rest, s)),
+    }
+}
+
+type Mappings<'a> = HashMap<&'a str, (&'a str, &'a str)>;
+
+/// The nodes of the map and the left/right instructions for walking them.
 #[derive(Debug)]
-struct Cycle {
-    offsets: Vec<u64>,
-    length: u64,
+pub struct Network<'a> {
+    steps: Vec<char>,
+    mappings: Mappings<'a>,
 }
 
-fn find_cycle(steps: &[char], mappings: &HashMap<&str, (&str, &str)>, start_node: &str) -> Cycle {
-    let mut n: usize = 0;
+impl<'a> Network<'a> {
+    pub fn parse(lines: &[&'a str]) -> Result<Network<'a>, String> {
+        let (steps, mappings) = parse_input(lines)?;
+        for (l, r) in mappings.values() {
+            if !mappings.contains_key(l) || !mappings.contains_key(r) {
+                return Err("reference to unknown node".to_string());
+            }
+        }
+        Ok(Network { steps, mappings })
+    }
+
+    /// The nodes in the set, sorted by name.
+    pub fn nodes(&self, set: &NodeSet) -> Vec<&'a str> {
+        let mut nodes: Vec<&str> = self
+            .mappings
+            .keys()
+            .copied()
+            .filter(|n| set.contains(n))
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    /// The steps at which a walker starting at `start` is on a goal node.
+    pub fn cycle(&self, start: &str, goals: &NodeSet) -> Result<Cycle, String> {
+        Ok(self.walk(start, goals)?.0)
+    }
+
+    /// The nodes a walker starting at `start` steps on by following the instructions, sorted by
+    /// name.
+    pub fn visited(&self, start: &str) -> Result<Vec<&'a str>, String> {
+        Ok(self.walk(start, &NodeSet::Nodes(vec![]))?.1)
+    }
+
+    /// Follows the instructions from `start` until the walker is back in a state it has been in,
+    /// and returns its cycle together with the nodes it stepped on.
+    fn walk(&self, start: &str, goals: &NodeSet) -> Result<(Cycle, Vec<&'a str>), String> {
+        let mut current = *self
+            .mappings
+            .get_key_value(start)
+            .ok_or(format!("unknown node {start}"))?
+            .0;
+        let mut n: usize = 0;
+
+        let mut cache: HashMap<(usize, &'a str), usize> = HashMap::new();
+        let mut hits: Vec<u64> = Vec::new();
+
+        loop {
+            let s = n % self.steps.len();
+            if let Some(&start) = cache.get(&(s, current)) {
+                let start = start as u64;
+                let (transient, offsets) = hits.into_iter().partition(|&h| h < start);
+                let cycle = Cycle {
+                    transient,
+                    offsets,
+                    start,
+                    length: n as u64 - start,
+                };
+                let mut visited: Vec<&str> = cache.into_keys().map(|(_, node)| node).collect();
+                visited.sort();
+                visited.dedup();
+                return Ok((cycle, visited));
+            }
+            cache.insert((s, current), n);
+            if goals.contains(current) {
+                hits.push(n as u64);
+            }
+
+            let (l, r) = self.mappings[current];
+            current = if self.steps[s] == 'R' { r } else { l };
+            n += 1;
+        }
+    }
+
+    /// The number of steps from `start` to the first goal node, or `None` if the walker never
+    /// gets to one.
+    pub fn steps_to_goal(&self, start: &str, goals: &NodeSet) -> Result<Option<u64>, String> {
+        Ok(self.cycle(start, goals)?.first())
+    }
 
-    let mut cache: HashMap<(usize, &str), usize> = HashMap::new();
-    let mut offsets: Vec<u64> = Vec::new();
+    /// The start nodes whose walkers never get to a goal node.
+    pub fn lost_walkers(&self, starts: &NodeSet, goals: &NodeSet) -> Vec<&'a str> {
+        self.nodes(starts)
+            .into_iter()
+            .filter(|n| self.steps_to_goal(n, goals) == Ok(None))
+            .collect()
+    }
 
-    let mut current = start_node;
-    loop {
-        let s = n % steps.len();
-        if let Some(&start) = cache.get(&(s, current)) {
-            return Cycle {
-                offsets,
-                length: (n - start) as u64,
-            };
+    /// The first step at which the walkers from all start nodes are on goal nodes at the same
+    /// time, or `None` if that never happens.
+    pub fn alignment(&self, starts: &NodeSet, goals: &NodeSet) -> Result<Option<u64>, String> {
+        let mut combined: Option<Cycle> = None;
+        for node in self.nodes(starts) {
+            let cycle = self.cycle(node, goals)?;
+            combined = Some(match combined {
+                None => cycle,
+                Some(c) => c.intersect(&cycle)?, // Chinese Remainder Theorem, two cycles at a time
+            });
         }
-        cache.insert((s, current), n);
-        if current.ends_with('Z') {
-            offsets.push(n as u64);
+
+        Ok(combined.ok_or("no start nodes")?.first())
+    }
+}
+
+/// A walker: the nodes it steps on, and when it is on a goal node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Walker<'a> {
+    pub start: &'a str,
+    /// The nodes the walker steps on by following the instructions, sorted by name.
+    pub visited: Vec<&'a str>,
+    pub cycle: Cycle,
+}
+
+impl Walker<'_> {
+    /// The number of steps before the walker starts repeating itself.
+    pub fn transient_length(&self) -> u64 {
+        self.cycle.start
+    }
+
+    pub fn cycle_length(&self) -> u64 {
+        self.cycle.length
+    }
+
+    /// The steps in the repeating part at which the walker is on a goal node.
+    pub fn goal_offsets(&self) -> &[u64] {
+        &self.cycle.offsets
+    }
+}
+
+/// The ways in which a network breaks the assumption that every walker has its own loop with
+/// exactly one goal node on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Irregularity<'a> {
+    /// The walker is never on a goal node once it is looping.
+    NoGoal { start: &'a str },
+    /// The walker is on a goal node more than once per loop.
+    SeveralGoals { start: &'a str, offsets: Vec<u64> },
+    /// The walker is on a goal node before it starts looping.
+    TransientGoals { start: &'a str, steps: Vec<u64> },
+    /// Both walkers step on the node.
+    SharedNode { starts: (&'a str, &'a str), node: &'a str },
+}
+
+impl Display for Irregularity<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Irregularity::NoGoal { start } => write!(f, "{} never loops over a goal", start),
+            Irregularity::SeveralGoals { start, offsets } => {
+                write!(f, "{} loops over several goals at steps {:?}", start, offsets)
+            }
+            Irregularity::TransientGoals { start, steps } => {
+                write!(f, "{} is on a goal before looping at steps {:?}", start, steps)
+            }
+            Irregularity::SharedNode { starts, node } => {
+                write!(f, "{} and {} both step on {}", starts.0, starts.1, node)
+            }
         }
+    }
+}
+
+/// Fill colours for the start nodes in the DOT export, reused when there are more start nodes.
+const COLORS: [&str; 8] = [
+    "lightblue", "lightpink", "palegreen", "khaki", "plum", "lightsalmon", "aquamarine", "wheat",
+];
+
+impl<'a> Network<'a> {
+    /// A walker for every start node, sorted by the name of the start node.
+    pub fn walkers(&self, starts: &NodeSet, goals: &NodeSet) -> Vec<Walker<'a>> {
+        self.nodes(starts)
+            .into_iter()
+            .map(|start| {
+                let (cycle, visited) = self.walk(start, goals).unwrap();
+                Walker {
+                    start,
+                    visited,
+                    cycle,
+                }
+            })
+            .collect()
+    }
+
+    /// Everything that stops the walkers from being solved as independent loops with one goal
+    /// each.
+    pub fn irregularities(&self, starts: &NodeSet, goals: &NodeSet) -> Vec<Irregularity<'a>> {
+        let walkers = self.walkers(starts, goals);
+        let mut found = Vec::new();
 
-        let go_right = steps[s] == 'R';
-        current = mappings
-            .get(&current)
-            .map(|(l, r)| if go_right { r } else { l })
-            .unwrap();
-        n += 1;
-    }
-}
-
-/// Basic implementation of the Chinese Remainder Theorem for two equations
-fn intersect(l: Cycle, r: Cycle) -> Cycle {
-    let mut offsets = Vec::new();
-    for la in l.offsets {
-        for &ra in r.offsets.iter() {
-            for n in 0..r.length {
-                let x = la + n * l.length;
-                if (x + r.length - ra).is_multiple_of(r.length) {
-                    // found first match
-                    offsets.push(x);
-                    break;
+        let mut owners: HashMap<&str, &str> = HashMap::new();
+        for w in walkers.iter() {
+            if !w.cycle.transient.is_empty() {
+                found.push(Irregularity::TransientGoals {
+                    start: w.start,
+                    steps: w.cycle.transient.clone(),
+                });
+            }
+            match w.cycle.offsets.len() {
+                0 => found.push(Irregularity::NoGoal { start: w.start }),
+                1 => (),
+                _ => found.push(Irregularity::SeveralGoals {
+                    start: w.start,
+                    offsets: w.cycle.offsets.clone(),
+                }),
+            }
+            for &node in w.visited.iter() {
+                if let Some(&owner) = owners.get(node) {
+                    found.push(Irregularity::SharedNode {
+                        starts: (owner, w.start),
+                        node,
+                    });
+                } else {
+                    owners.insert(node, w.start);
                 }
             }
         }
+        found
     }
-    Cycle {
-        offsets,
-        length: l.length * r.length / l.length.gcd_binary(r.length),
+
+    /// The network in Graphviz DOT format, with the nodes visited from every start node in
+    /// their own colour. Nodes visited from several start nodes are grey.
+    pub fn to_dot(&self, starts: &NodeSet, goals: &NodeSet) -> String {
+        let mut colors: HashMap<&str, &str> = HashMap::new();
+        for (i, start) in self.nodes(starts).into_iter().enumerate() {
+            for node in self.visited(start).unwrap() {
+                let color = COLORS[i % COLORS.len()];
+                colors
+                    .entry(node)
+                    .and_modify(|c| *c = "lightgrey")
+                    .or_insert(color);
+            }
+        }
+
+        let mut dot = String::from("digraph network {\n");
+        for node in self.nodes(&NodeSet::Pattern("*".into())) {
+            let mut attrs = Vec::new();
+            if let Some(color) = colors.get(node) {
+                attrs.push(format!("style=filled, fillcolor={color}"));
+            }
+            if starts.contains(node) {
+                attrs.push("shape=box".to_string());
+            }
+            if goals.contains(node) {
+                attrs.push("peripheries=2".to_string());
+            }
+            if attrs.is_empty() {
+                writeln!(dot, "  \"{node}\";").unwrap();
+            } else {
+                writeln!(dot, "  \"{node}\" [{}];", attrs.join(", ")).unwrap();
+            }
+        }
+        for node in self.nodes(&NodeSet::Pattern("*".into())) {
+            let (l, r) = self.mappings[node];
+            if l == r {
+                writeln!(dot, "  \"{node}\" -> \"{l}\" [label=\"LR\"];").unwrap();
+            } else {
+                writeln!(dot, "  \"{node}\" -> \"{l}\" [label=\"L\"];").unwrap();
+                writeln!(dot, "  \"{node}\" -> \"{r}\" [label=\"R\"];").unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 }
 
-pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
-    let steps: Vec<char> = lines[0].chars().collect();
+fn parse_input<'a>(lines: &[&'a str]) -> Result<(Vec<char>, Mappings<'a>), String> {
+    let steps: Vec<char> = lines.first().ok_or("no lines")?.chars().collect();
+    if steps.is_empty() || steps.iter().any(|&c| c != 'L' && c != 'R') {
+        return Err("the first line should only contain 'L' and 'R'".to_string());
+    }
+    if lines.get(1) != Some(&"") {
+        return Err("expected an empty second line".to_string());
+    }
 
-    let mut mappings: HashMap<&str, (&str, &str)> = HashMap::new();
+    let mut mappings: Mappings = HashMap::new();
 
     for line in lines[2..].iter() {
-        let (from, to) = line.split_once('=').unwrap();
+        let (from, to) = line.split_once('=').ok_or("missing '='")?;
         let (left, right) = to
             .trim()
             .strip_prefix('(')
-            .unwrap()
+            .ok_or("missing '('")?
             .strip_suffix(')')
-            .unwrap()
+            .ok_or("missing ')'")?
             .split_once(',')
-            .unwrap();
+            .ok_or("missing ','")?;
 
         mappings.insert(from.trim(), (left.trim(), right.trim()));
     }
 
+    Ok((steps, mappings))
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    let network = Network::parse(lines)?;
+    if network.mappings.is_empty() {
+        return Err("no nodes".to_string());
+    }
+    Ok(())
+}
+
+pub fn run(lines: Vec<&str>, part1: bool) -> u64 {
     if part1 {
-        let mut current = "AAA";
-        let mut num_steps = 0;
-        while current != "ZZZ" {
-            let &(l, r) = mappings.get(current).unwrap();
-            if steps[num_steps % steps.len()] == 'R' {
-                current = r;
-            } else {
-                current = l;
-            }
-            num_steps += 1;
-        }
-        num_steps as u64
-    } else {
-        mappings
-            .keys()
-            .copied()
-            .filter(|s| s.ends_with('A'))
-            .map(|node| find_cycle(&steps, &mappings, node))
-            .reduce(intersect) // Chinese Remainder Theorem applied two cycles at a time
+        let network = Network::parse(&lines).unwrap();
+        network
+            .steps_to_goal("AAA", &NodeSet::node("ZZZ"))
             .unwrap()
-            .offsets
-            .into_iter()
-            .min()
+            .expect("there is no way from AAA to ZZZ")
+    } else {
+        ghost_alignment(&lines)
             .unwrap()
+            .expect("the ghosts are never all on 'Z' nodes at once")
     }
 }
+
+/// The first step at which all ghosts are on nodes ending with 'Z' at the same time,
+/// or `None` if that never happens.
+pub fn ghost_alignment(lines: &[&str]) -> Result<Option<u64>, String> {
+    Network::parse(lines)?.alignment(&NodeSet::Suffix("A".into()), &NodeSet::Suffix("Z".into()))
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let net = Network::parse(&lines).unwrap();
+    let goals = NodeSet::Suffix("Z".into());
+
+    let network = net
+        .nodes(&NodeSet::Pattern("*".into()))
+        .iter()
+        .map(|n| format!("{} = {:?}\n", n, net.mappings[n]))
+        .collect();
+    let cycles = net
+        .nodes(&NodeSet::Suffix("A".into()))
+        .iter()
+        .map(|n| format!("{}: {:?}\n", n, net.cycle(n, &goals).unwrap()))
+        .collect();
+
+    let starts = NodeSet::Suffix("A".into());
+    let irregularities = net
+        .irregularities(&starts, &goals)
+        .iter()
+        .map(|i| format!("{}\n", i))
+        .collect();
+    let dot = net.to_dot(&starts, &goals);
+
+    vec![
+        ("network", network),
+        ("cycles", cycles),
+        ("irregularities", irregularities),
+        ("dot", dot),
+    ]
+}
@@ -1,27 +1,137 @@
-fn predict(numbers: &[i64], forward: bool) -> i64 {
-    if numbers.iter().all(|&n| n == 0) {
-        return 0;
+use num::{BigInt, Zero};
+
+/// A sequence of values of a polynomial at the indices 0, 1, 2, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    values: Vec<i64>,
+    /// The first value of every row of differences, down to the last row that isn't all zeros.
+    coefficients: Vec<BigInt>,
+}
+
+impl Sequence {
+    /// Fails if the differences don't become all zeros while there are still values left to
+    /// check, because then any polynomial of high enough degree would fit.
+    pub fn new(values: &[i64]) -> Result<Sequence, String> {
+        let mut row: Vec<BigInt> = values.iter().map(|&v| BigInt::from(v)).collect();
+        let mut coefficients = Vec::new();
+        while !row.iter().all(|v| v.is_zero()) {
+            coefficients.push(row[0].clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+        if row.is_empty() {
+            return Err(format!(
+                "{:?} is not a polynomial of degree below {}",
+                values,
+                values.len().saturating_sub(1)
+            ));
+        }
+        if coefficients.is_empty() {
+            coefficients.push(BigInt::zero());
+        }
+        Ok(Sequence {
+            values: values.to_vec(),
+            coefficients,
+        })
     }
 
-    let mut diffs = Vec::with_capacity(numbers.len() - 1);
-    for i in 0..numbers.len() - 1 {
-        diffs.push(numbers[i + 1] - numbers[i]);
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
     }
 
-    let p = predict(&diffs, forward);
-    if forward {
-        numbers[numbers.len() - 1] + p
-    } else {
-        numbers[0] - p
+    /// The coefficients `c` of the Newton form `f(x) = sum(c[k] * binomial(x, k))`.
+    pub fn coefficients(&self) -> &[BigInt] {
+        &self.coefficients
     }
+
+    /// The value at any index, also negative ones or ones past the end.
+    pub fn at(&self, index: i64) -> BigInt {
+        let x = BigInt::from(index);
+        let mut binomial = BigInt::from(1);
+        let mut res = BigInt::zero();
+        for (k, c) in self.coefficients.iter().enumerate() {
+            if k > 0 {
+                // binomial(x, k) = binomial(x, k - 1) * (x - k + 1) / k, which divides exactly
+                binomial = binomial * (&x - k + 1) / k;
+            }
+            res += c * &binomial;
+        }
+        res
+    }
+
+    /// The value after the last one.
+    pub fn next(&self) -> BigInt {
+        self.at(self.values.len() as i64)
+    }
+
+    /// The value before the first one.
+    pub fn previous(&self) -> BigInt {
+        self.at(-1)
+    }
+}
+
+fn parse_numbers(line: &str) -> Result<Vec<i64>, String> {
+    line.split(' ')
+        .map(|s| s.parse::<i64>().map_err(|e| format!("invalid number {}: {}", s, e)))
+        .collect()
+}
+
+pub fn validate(lines: &[&str]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("no sequences".to_string());
+    }
+    for line in lines {
+        let numbers = parse_numbers(line)?;
+        if numbers.len() < 2 {
+            return Err("sequences need at least two numbers".to_string());
+        }
+        Sequence::new(&numbers)?;
+    }
+    Ok(())
 }
 
 pub fn run(lines: Vec<&str>, part1: bool) -> i64 {
-    let mut res = 0;
+    let mut res = BigInt::zero();
     for line in lines {
-        let numbers: Vec<i64> = line.split(' ').map(|s| s.parse::<i64>().unwrap()).collect();
-        let p = predict(&numbers, part1);
-        res += p;
+        let sequence = Sequence::new(&parse_numbers(line).unwrap()).unwrap();
+        res += if part1 {
+            sequence.next()
+        } else {
+            sequence.previous()
+        };
+    }
+    res.try_into().unwrap()
+}
+
+pub fn snapshots(lines: Vec<&str>) -> Vec<(&'static str, String)> {
+    let mut text = String::new();
+    let mut polynomials = String::new();
+    for line in lines {
+        let mut row = parse_numbers(line).unwrap();
+        let sequence = Sequence::new(&row).unwrap();
+        text += &format!(
+            "{} => back {} forward {}\n",
+            line,
+            sequence.previous(),
+            sequence.next()
+        );
+        while !row.iter().all(|&n| n == 0) {
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+            text += &format!("  {:?}\n", row);
+        }
+
+        let coefficients: Vec<String> = sequence
+            .coefficients()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        polynomials += &format!(
+            "{} => degree {}, coefficients [{}], f(-1000) = {}, f(1000) = {}\n",
+            line,
+            sequence.degree(),
+            coefficients.join(", "),
+            sequence.at(-1000),
+            sequence.at(1000)
+        );
     }
-    res
+    vec![("differences", text), ("polynomials", polynomials)]
 }
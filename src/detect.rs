@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use crate::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Detection {
+    pub day: u8,
+    /// Between 0 and 1. The confidences of all detections for an input add up to 1.
+    pub confidence: f64,
+}
+
+fn validate(day: u8, lines: &[&str]) -> Result<(), String> {
+    match day {
+        1 => d1::validate(lines),
+        2 => d2::validate(lines),
+        3 => d3::validate(lines),
+        4 => d4::validate(lines),
+        5 => d5::validate(lines),
+        6 => d6::validate(lines),
+        7 => d7::validate(lines),
+        8 => d8::validate(lines),
+        9 => d9::validate(lines),
+        10 => d10::validate(lines),
+        11 => d11::validate(lines),
+        12 => d12::validate(lines),
+        13 => d13::validate(lines),
+        14 => d14::validate(lines),
+        15 => d15::validate(lines),
+        16 => d16::validate(lines),
+        17 => d17::validate(lines),
+        18 => d18::validate(lines),
+        19 => d19::validate(lines),
+        20 => d20::validate(lines),
+        21 => d21::validate(lines),
+        22 => d22::validate(lines),
+        23 => d23::validate(lines),
+        24 => d24::validate(lines),
+        25 => d25::validate(lines),
+        _ => Err(format!("invalid day: {}", day)),
+    }
+}
+
+/// The characters a free-form input (mostly grids) is made of, or `None` if the format has enough
+/// structure that passing validation is a strong signal on its own.
+fn alphabet(day: u8) -> Option<&'static str> {
+    match day {
+        1 => Some("abcdefghijklmnopqrstuvwxyz0123456789"),
+        3 => Some("0123456789.*#+$/@=%-&"),
+        10 => Some("|-LJ7F.S"),
+        11 => Some(".#"),
+        13 => Some(".#"),
+        14 => Some(".O#"),
+        16 => Some(".\\/|-"),
+        17 => Some("123456789"),
+        21 => Some(".#S"),
+        23 => Some(".#><^v"),
+        _ => None,
+    }
+}
+
+/// How much of the day's alphabet shows up in the input. Inputs that only use a few characters of a
+/// large alphabet are more likely to belong to some other day with a smaller alphabet.
+fn score(day: u8, input: &str) -> f64 {
+    match alphabet(day) {
+        Some(alphabet) => {
+            let used: HashSet<char> = input.chars().collect();
+            let hits = alphabet.chars().filter(|c| used.contains(c)).count();
+            hits as f64 / alphabet.chars().count() as f64
+        }
+        None => 1.0,
+    }
+}
+
+/// Returns the days whose parsers accept the input, most likely first.
+pub fn classify(input: &str) -> Vec<Detection> {
+    let lines: Vec<&str> = input.lines().collect();
+
+    let scores: Vec<(u8, f64)> = crate::runner::DAYS
+        .filter(|&day| validate(day, &lines).is_ok())
+        .map(|day| (day, score(day, input)))
+        .filter(|&(_, s)| s > 0.0)
+        .collect();
+
+    let total: f64 = scores.iter().map(|(_, s)| s).sum();
+
+    let mut detections: Vec<Detection> = scores
+        .into_iter()
+        .map(|(day, s)| Detection {
+            day,
+            confidence: s / total,
+        })
+        .collect();
+    detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then(a.day.cmp(&b.day)));
+    detections
+}
+
+/// The most likely day, if any parser accepts the input.
+pub fn detect_day(input: &str) -> Option<Detection> {
+    classify(input).into_iter().next()
+}
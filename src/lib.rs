@@ -23,3 +23,11 @@ pub mod d22;
 pub mod d23;
 pub mod d24;
 pub mod d25;
+pub mod alloc_stats;
+pub mod budget;
+pub mod detect;
+pub mod profile;
+pub mod rng;
+pub mod runner;
+pub mod snapshot;
+pub mod watch;
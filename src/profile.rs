@@ -0,0 +1,170 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    alloc_stats::{AllocStats, measure},
+    runner::{Part, solve},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: Part,
+    pub time: Duration,
+    pub alloc: AllocStats,
+}
+
+/// Solves the day and measures how long it took and how much it allocated.
+/// Allocations are only counted if `CountingAllocator` is installed.
+pub fn profile(day: u8, part: Part, input: &str) -> (Result<String, String>, Measurement) {
+    let start = Instant::now();
+    let (answer, alloc) = measure(|| solve(day, part, input));
+    let time = start.elapsed();
+
+    (
+        answer,
+        Measurement {
+            day,
+            part,
+            time,
+            alloc,
+        },
+    )
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Regression {
+    pub day: u8,
+    pub part: Part,
+    pub metric: &'static str,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {} {}: {} went from {} to {}",
+            self.day, self.part, self.metric, self.baseline, self.current
+        )
+    }
+}
+
+/// Measurements of several days, which can be saved and later used as a baseline.
+///
+/// The text format has one line per day and part:
+/// `day part time_us peak_bytes allocations bytes_allocated`
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Report {
+    pub fn parse(text: &str) -> Result<Report, String> {
+        let mut measurements = Vec::new();
+
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let nums: Vec<u64> = line
+                .split_whitespace()
+                .map(|s| {
+                    s.parse::<u64>()
+                        .map_err(|e| format!("invalid number {}: {}", s, e))
+                })
+                .collect::<Result<_, _>>()?;
+
+            let [day, part, time_us, peak_bytes, allocations, bytes_allocated] = nums[..] else {
+                return Err(format!("expected 6 numbers: {}", line));
+            };
+
+            let day = u8::try_from(day).map_err(|_| format!("invalid day {}: {}", day, line))?;
+            measurements.push(Measurement {
+                day,
+                part: Part::try_from(part.to_string().as_str())?,
+                time: Duration::from_micros(time_us),
+                alloc: AllocStats {
+                    allocations,
+                    bytes_allocated,
+                    peak_bytes,
+                },
+            });
+        }
+
+        Ok(Report { measurements })
+    }
+
+    /// Lists every metric that grew by more than `tolerance` (0.1 means 10 %) compared to the baseline.
+    /// Times below one millisecond are too noisy to compare and are ignored.
+    pub fn regressions(&self, baseline: &Report, tolerance: f64) -> Vec<Regression> {
+        let mut res = Vec::new();
+
+        for m in self.measurements.iter() {
+            let Some(b) = baseline
+                .measurements
+                .iter()
+                .find(|b| b.day == m.day && b.part == m.part)
+            else {
+                continue;
+            };
+
+            let metrics = [
+                (
+                    "time_us",
+                    b.time.as_micros() as u64,
+                    m.time.as_micros() as u64,
+                ),
+                ("peak_bytes", b.alloc.peak_bytes, m.alloc.peak_bytes),
+                ("allocations", b.alloc.allocations, m.alloc.allocations),
+                (
+                    "bytes_allocated",
+                    b.alloc.bytes_allocated,
+                    m.alloc.bytes_allocated,
+                ),
+            ];
+
+            for (metric, before, after) in metrics {
+                if metric == "time_us" && after < 1000 {
+                    continue;
+                }
+                if after as f64 > before as f64 * (1.0 + tolerance) {
+                    res.push(Regression {
+                        day: m.day,
+                        part: m.part,
+                        metric,
+                        baseline: before,
+                        current: after,
+                    });
+                }
+            }
+        }
+
+        res
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "# day part time_us peak_bytes allocations bytes_allocated"
+        )?;
+        for m in self.measurements.iter() {
+            writeln!(
+                f,
+                "{} {} {} {} {} {}",
+                m.day,
+                if m.part == Part::One { 1 } else { 2 },
+                m.time.as_micros(),
+                m.alloc.peak_bytes,
+                m.alloc.allocations,
+                m.alloc.bytes_allocated
+            )?;
+        }
+        Ok(())
+    }
+}
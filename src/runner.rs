@@ -0,0 +1,117 @@
+use std::{
+    fmt::{Debug, Display},
+    panic,
+};
+
+use crate::{
+    budget::{Budget, TimedOut},
+    *,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "part 1"),
+            Part::Two => write!(f, "part 2"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Part {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("invalid part: {}", s)),
+        }
+    }
+}
+
+pub const DAYS: std::ops::RangeInclusive<u8> = 1..=25;
+
+/// Solves the given day and part using the same arguments as the puzzle (e.g. 64 steps for d21 part 1).
+/// Panics inside the solver are caught and returned as errors, since hand-crafted inputs often trigger them.
+pub fn solve(day: u8, part: Part, input: &str) -> Result<String, String> {
+    solve_with_budget(day, part, input, &Budget::unlimited())
+}
+
+/// Like `solve`, but gives up with a "timed out" error when the budget runs out.
+/// The slow days check the budget inside their main loops, the others only before starting.
+pub fn solve_with_budget(
+    day: u8,
+    part: Part,
+    input: &str,
+    budget: &Budget,
+) -> Result<String, String> {
+    if !DAYS.contains(&day) {
+        return Err(format!("invalid day: {}", day));
+    }
+    if day == 25 && part == Part::Two {
+        return Err("day 25 has no second part".to_string());
+    }
+
+    panic::catch_unwind(|| solve_unchecked(day, part, input, budget)).unwrap_or_else(|e| {
+        if let Some(s) = e.downcast_ref::<&str>() {
+            Err(s.to_string())
+        } else if let Some(s) = e.downcast_ref::<String>() {
+            Err(s.clone())
+        } else {
+            Err("solver panicked".to_string())
+        }
+    })
+}
+
+fn solve_unchecked(day: u8, part: Part, input: &str, budget: &Budget) -> Result<String, String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let part1 = part == Part::One;
+
+    budget.check(|| ()).map_err(|e| e.to_string())?;
+
+    fn done<T: ToString, P: Debug>(res: Result<T, TimedOut<P>>) -> Result<String, String> {
+        res.map(|v| v.to_string()).map_err(|e| e.to_string())
+    }
+
+    let res = match day {
+        1 => d1::run(lines, !part1).to_string(),
+        2 => d2::run(lines, part1).to_string(),
+        3 => d3::run(lines, part1).to_string(),
+        4 => d4::run(lines, part1).to_string(),
+        5 => d5::run(lines, part1).to_string(),
+        6 => d6::run(lines, part1).to_string(),
+        7 => d7::run(lines, part1).to_string(),
+        8 => d8::run(lines, part1).to_string(),
+        9 => d9::run(lines, part1).to_string(),
+        10 => d10::run(lines, part1).to_string(),
+        11 => d11::run(lines, if part1 { 2 } else { 1_000_000 }).to_string(),
+        12 => d12::run(lines, part1).to_string(),
+        13 => d13::run(lines, part1).to_string(),
+        14 => return done(d14::run_with_budget(lines, part1, budget)),
+        15 => d15::run(lines.first().copied().unwrap_or(""), part1).to_string(),
+        16 => return done(d16::run_with_budget(lines, part1, budget)),
+        17 => return done(d17::run_with_budget(lines, part1, budget)),
+        18 => d18::run(lines, part1).to_string(),
+        19 => d19::run(lines, part1).to_string(),
+        20 => return done(d20::run_with_budget(lines, part1, budget)),
+        21 => {
+            let steps = if part1 { 64 } else { 26501365 };
+            return done(d21::run_with_budget(lines, steps, !part1, budget));
+        }
+        22 => return done(d22::run_with_budget(lines, !part1, budget)),
+        23 => return done(d23::run_with_budget(lines, !part1, budget)),
+        24 => {
+            let (lo, hi) = (200000000000000, 400000000000000);
+            return done(d24::run_with_budget(lines, lo, hi, !part1, budget));
+        }
+        25 => return done(d25::run_with_budget(lines, budget)),
+        _ => unreachable!(),
+    };
+    Ok(res)
+}
@@ -0,0 +1,87 @@
+use std::{env, fs, path::Path};
+
+/// Set this environment variable to `1` to write the current values as the new snapshots.
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+fn updating() -> bool {
+    env::var(UPDATE_VAR).is_ok_and(|v| v == "1")
+}
+
+/// Describes the first line that differs, or `None` if the texts are equal.
+pub fn diff(expected: &str, actual: &str) -> Option<String> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+
+    for line_no in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return None,
+            (e, a) if e == a => continue,
+            (e, a) => {
+                return Some(format!(
+                    "line {}:\n  expected: {}\n  actual:   {}",
+                    line_no,
+                    e.unwrap_or("<end of snapshot>"),
+                    a.unwrap_or("<end of output>")
+                ));
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Compares the value with the golden file `<dir>/<name>.snap`.
+pub fn check(dir: impl AsRef<Path>, name: &str, actual: &str) -> Result<(), String> {
+    let path = dir.as_ref().join(format!("{}.snap", name));
+
+    if updating() {
+        return fs::write(&path, actual).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    let expected = fs::read_to_string(&path).map_err(|e| {
+        format!(
+            "{}: {} (run with {}=1 to create it)",
+            path.display(),
+            e,
+            UPDATE_VAR
+        )
+    })?;
+
+    match diff(&expected, actual) {
+        None => Ok(()),
+        Some(d) => Err(format!("snapshot {} changed at {}", path.display(), d)),
+    }
+}
+
+/// Checks all snapshots and panics with a report of every one that changed.
+pub fn assert_snapshots(dir: impl AsRef<Path>, snapshots: &[(&str, String)]) {
+    let errors: Vec<String> = snapshots
+        .iter()
+        .filter_map(|(name, actual)| check(dir.as_ref(), name, actual).err())
+        .collect();
+
+    if !errors.is_empty() {
+        panic!(
+            "{}\n(run with {}=1 to accept the changes)",
+            errors.join("\n"),
+            UPDATE_VAR
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_first_changed_line() {
+        assert_eq!(diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nx\nc\n"),
+            Some("line 2:\n  expected: b\n  actual:   x".to_string())
+        );
+        assert_eq!(
+            diff("a\n", "a\nb\n"),
+            Some("line 2:\n  expected: <end of snapshot>\n  actual:   b".to_string())
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::runner::{solve, Part};
+
+pub type Answer = Result<String, String>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    pub part: Part,
+    pub previous: Option<Answer>,
+    pub current: Answer,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn show(a: &Answer) -> String {
+            match a {
+                Ok(v) => v.clone(),
+                Err(e) => format!("error ({})", e),
+            }
+        }
+
+        let current = show(&self.current);
+        match &self.previous {
+            None => write!(f, "{}: {}", self.part, current),
+            Some(p) if *p == self.current => write!(f, "{}: {} (unchanged)", self.part, current),
+            Some(p) => write!(f, "{}: {} -> {}", self.part, show(p), current),
+        }
+    }
+}
+
+/// Re-solves a day whenever the modification time of its input file changes.
+pub struct Watcher {
+    path: PathBuf,
+    day: u8,
+    parts: Vec<Part>,
+    modified: Option<SystemTime>,
+    answers: Vec<Option<Answer>>,
+}
+
+impl Watcher {
+    pub fn new(path: impl Into<PathBuf>, day: u8, parts: Vec<Part>) -> Self {
+        let answers = parts.iter().map(|_| None).collect();
+        Self {
+            path: path.into(),
+            day,
+            parts,
+            modified: None,
+            answers,
+        }
+    }
+
+    /// Returns `None` if the file did not change since the last call, otherwise the new answers
+    /// compared to the previous ones. The first call always solves.
+    pub fn poll(&mut self) -> io::Result<Option<Vec<Change>>> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if self.modified == Some(modified) {
+            return Ok(None);
+        }
+        self.modified = Some(modified);
+
+        let input = fs::read_to_string(&self.path)?;
+
+        let mut changes = Vec::with_capacity(self.parts.len());
+        for (&part, last) in self.parts.iter().zip(self.answers.iter_mut()) {
+            let current = solve(self.day, part, &input);
+            changes.push(Change {
+                part,
+                previous: last.replace(current.clone()),
+                current,
+            });
+        }
+        Ok(Some(changes))
+    }
+}
+
+/// Polls the file forever, printing the answers every time it changes.
+pub fn watch(watcher: &mut Watcher, interval: Duration) -> io::Result<()> {
+    loop {
+        match watcher.poll() {
+            Ok(Some(changes)) => {
+                println!("--- {} (day {}) ---", watcher.path.display(), watcher.day);
+                for c in changes {
+                    println!("{}", c);
+                }
+            }
+            Ok(None) => {}
+            // the file is often missing for a moment while an editor saves it
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        thread::sleep(interval);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use advent23::{
+    alloc_stats::{AllocStats, CountingAllocator, measure},
+    profile::{Measurement, Report, profile},
+    runner::Part,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn counts_allocations() {
+    let (v, stats) = measure(|| {
+        let mut v: Vec<u64> = Vec::with_capacity(100);
+        v.push(1);
+        let tmp: Vec<u8> = vec![0; 1000];
+        drop(tmp);
+        v
+    });
+
+    assert_eq!(v, vec![1]);
+    assert_eq!(stats.allocations, 2);
+    assert_eq!(stats.bytes_allocated, 800 + 1000);
+    assert_eq!(stats.peak_bytes, 800 + 1000);
+}
+
+#[test]
+fn nested_measurements_keep_the_outer_peak() {
+    let (_, outer) = measure(|| {
+        let big: Vec<u8> = vec![0; 4000];
+        drop(big);
+        let (_, inner) = measure(|| vec![0u8; 100]);
+        assert_eq!(inner.peak_bytes, 100);
+    });
+
+    assert_eq!(outer.peak_bytes, 4000);
+}
+
+#[test]
+fn profiles_a_day() {
+    let input = include_str!("../d14/in1.txt");
+    let (answer, m) = profile(14, Part::Two, input);
+
+    assert_eq!(answer, Ok("64".to_string()));
+    assert!(m.alloc.allocations > 0);
+    assert!(m.alloc.peak_bytes > 0);
+    assert!(m.alloc.bytes_allocated >= m.alloc.peak_bytes);
+}
+
+fn measurement(day: u8, time_ms: u64, peak_bytes: u64) -> Measurement {
+    Measurement {
+        day,
+        part: Part::One,
+        time: Duration::from_millis(time_ms),
+        alloc: AllocStats {
+            allocations: 10,
+            bytes_allocated: 1000,
+            peak_bytes,
+        },
+    }
+}
+
+#[test]
+fn report_round_trip() {
+    let report = Report {
+        measurements: vec![measurement(1, 5, 100), measurement(22, 300, 4000)],
+    };
+    assert_eq!(Report::parse(&report.to_string()), Ok(report));
+}
+
+#[test]
+fn report_days_out_of_range_are_errors() {
+    assert!(Report::parse("256 1 5000 100 10 1000").is_err());
+}
+
+#[test]
+fn finds_regressions() {
+    let baseline = Report {
+        measurements: vec![measurement(14, 10, 1000), measurement(21, 10, 1000)],
+    };
+    let current = Report {
+        measurements: vec![measurement(14, 11, 1000), measurement(21, 10, 2000)],
+    };
+
+    let regressions = current.regressions(&baseline, 0.2);
+
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].day, 21);
+    assert_eq!(regressions[0].metric, "peak_bytes");
+}
@@ -0,0 +1,63 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use advent23::{
+    budget::{Budget, CancelToken, TimedOut},
+    runner::{solve_with_budget, Part},
+};
+
+#[test]
+fn unlimited_budget_never_expires() {
+    assert!(!Budget::unlimited().expired());
+    assert_eq!(Budget::unlimited().check(|| 1), Ok(()));
+}
+
+#[test]
+fn deadline_in_the_past_expires() {
+    let budget = Budget::unlimited().with_deadline(Instant::now());
+    assert_eq!(budget.check(|| 1), Err(TimedOut { partial: 1 }));
+}
+
+#[test]
+fn max_checks_expires_budget() {
+    let budget = Budget::unlimited().with_max_checks(2);
+    assert_eq!(budget.check(|| 1), Ok(()));
+    assert_eq!(budget.check(|| 2), Ok(()));
+    assert!(budget.expired());
+    assert_eq!(budget.check(|| 3), Err(TimedOut { partial: 3 }));
+}
+
+#[test]
+fn cancel_token_expires_budget() {
+    let token = CancelToken::new();
+    let budget = Budget::unlimited().with_token(token.clone());
+    assert!(!budget.expired());
+    token.cancel();
+    assert!(budget.expired());
+}
+
+#[test]
+fn cancel_running_solver_from_another_thread() {
+    let token = CancelToken::new();
+    let budget = Budget::unlimited().with_token(token.clone());
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        token.cancel();
+    });
+
+    let start = Instant::now();
+    let res = solve_with_budget(24, Part::Two, include_str!("../d24/in2.txt"), &budget);
+    canceller.join().unwrap();
+
+    assert!(res.unwrap_err().starts_with("timed out"));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn fast_days_check_budget_before_starting() {
+    let budget = Budget::unlimited().with_timeout(Duration::ZERO);
+    assert!(solve_with_budget(1, Part::One, "1abc2", &budget).is_err());
+}
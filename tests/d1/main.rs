@@ -1,4 +1,9 @@
-use advent23::d1::run;
+use advent23::{
+    d1::{
+        Combination, FoundDigit, Vocabulary, run, run_with_vocabulary, snapshots, try_run,
+    },
+    snapshot::assert_snapshots,
+};
 
 
 #[test]
@@ -24,3 +29,112 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), true), 54824);
 }
+
+#[test]
+fn overlapping_words() {
+    let english = Vocabulary::english();
+    assert_eq!(english.find_digits("eightwo"), vec![(0, 8), (4, 2)]);
+    assert_eq!(english.find_digits("xtwone3four"), vec![(1, 2), (3, 1), (6, 3), (7, 4)]);
+    assert_eq!(english.calibration_value("oneight"), Some(18));
+    assert_eq!(english.calibration_value("abc"), None);
+}
+
+#[test]
+fn other_locales() {
+    let swedish = Vocabulary::for_locale("sv").unwrap();
+    assert_eq!(swedish.calibration_value("xåttasjux"), Some(87));
+    assert_eq!(swedish.find_digits("tvåtta"), vec![(0, 2), (2, 8)]);
+
+    let german = Vocabulary::for_locale("de").unwrap();
+    assert_eq!(
+        run_with_vocabulary(vec!["zweifünf", "neun7sechsundzwanzig"], &german),
+        25 + 96
+    );
+
+    assert!(Vocabulary::for_locale("xx").is_err());
+}
+
+#[test]
+fn ignoring_case() {
+    let english = Vocabulary::english();
+    assert_eq!(english.calibration_value("Two1NINE"), Some(11));
+    assert_eq!(english.ignore_case().calibration_value("Two1NINE"), Some(29));
+
+    let swedish = Vocabulary::for_locale("sv").unwrap().ignore_case();
+    assert_eq!(swedish.find_digits("ÅTTA Fem"), vec![(0, 8), (6, 5)]);
+}
+
+#[test]
+fn custom_tokens() {
+    let vocabulary = Vocabulary::new(&[("twelve", "12"), ("dozen", "12"), ("x", "0")]).unwrap();
+    assert_eq!(vocabulary.find_digits("atwelvex"), vec![(1, 1), (1, 2), (7, 0)]);
+    assert_eq!(vocabulary.calibration_value("dozen"), Some(12));
+    assert_eq!(vocabulary.calibration_value("1"), None);
+
+    assert!(Vocabulary::new(&[("one", "")]).is_err());
+    assert!(Vocabulary::new(&[("one", "1a")]).is_err());
+    assert!(Vocabulary::new(&[("", "1")]).is_err());
+}
+
+#[test]
+fn same_answer_with_big_input_ignoring_case() {
+    let input = include_str!("in2.txt").to_uppercase();
+    let vocabulary = Vocabulary::english().ignore_case();
+    assert_eq!(run_with_vocabulary(input.lines().collect(), &vocabulary), 54824);
+}
+
+#[test]
+fn extracting_digits() {
+    let extraction = Vocabulary::english().extract("a1eightwo").unwrap();
+    assert_eq!(
+        extraction.digits,
+        vec![
+            FoundDigit {
+                digit: 1,
+                span: 1..2,
+                from_word: false
+            },
+            FoundDigit {
+                digit: 8,
+                span: 2..7,
+                from_word: true
+            },
+            FoundDigit {
+                digit: 2,
+                span: 6..9,
+                from_word: true
+            },
+        ]
+    );
+
+    assert_eq!(extraction.value(Combination::FirstLast), Ok(12));
+    assert_eq!(extraction.value(Combination::Concatenated), Ok(182));
+    assert_eq!(extraction.value(Combination::Sum), Ok(11));
+    let max = Combination::Custom(|d| Ok(*d.iter().max().unwrap() as u64));
+    assert_eq!(extraction.value(max), Ok(8));
+
+    let spans = Vocabulary::for_locale("sv").unwrap().ignore_case().extract("xÅTTA").unwrap();
+    assert_eq!(spans.digits[0].span, 1..6);
+}
+
+#[test]
+fn lines_without_digits_are_errors() {
+    let digits = Vocabulary::digits();
+    assert!(digits.extract("one").is_err());
+    assert_eq!(
+        try_run(&["a1b", "one", "2"], &digits, Combination::FirstLast),
+        Err("line 2: no digits in one".to_string())
+    );
+    assert_eq!(
+        try_run(&["a1b", "one", "2"], &Vocabulary::english(), Combination::Sum),
+        Ok(4)
+    );
+    assert!(try_run(&["123456789012345678901"], &digits, Combination::Concatenated).is_err());
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1b.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d1"), &snapshots(lines));
+}
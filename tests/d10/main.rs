@@ -1,4 +1,7 @@
-use advent23::d10::run;
+use advent23::{
+    d10::{Dir, Maze, PipeIssue, StartPipe, generate, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -35,3 +38,119 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 285);
 }
+
+const SEVERAL_LOOPS: [&str; 4] = ["F7.F-7", "LJ.|.|", "...L-J", ".-|F7."];
+
+#[test]
+fn finds_every_loop() {
+    let maze = Maze::parse(&SEVERAL_LOOPS).unwrap();
+    assert_eq!(maze.start(), None);
+
+    let loops = maze.loops();
+    assert_eq!(loops.len(), 2);
+    assert_eq!(loops[0].tiles, vec![(0, 0), (0, 1), (1, 1), (1, 0)]);
+    assert_eq!(maze.enclosed_area(&loops[0]), Ok(0));
+    assert_eq!(loops[1].len(), 8);
+    assert_eq!(loops[1].shoelace_area(), 4);
+    assert_eq!(maze.enclosed_area(&loops[1]), Ok(1));
+}
+
+#[test]
+fn lists_dangling_and_broken_pipes() {
+    let maze = Maze::parse(&SEVERAL_LOOPS).unwrap();
+    let dangling = |at, pipe, dir| PipeIssue::Dangling { at, pipe, dir };
+    assert_eq!(
+        maze.issues(),
+        vec![
+            dangling((1, 3), '-', Dir::Left),
+            PipeIssue::Broken {
+                at: (1, 3),
+                pipe: '-',
+                neighbor: (2, 3)
+            },
+            dangling((2, 3), '|', Dir::Up),
+            dangling((2, 3), '|', Dir::Down),
+            dangling((3, 3), 'F', Dir::Down),
+            dangling((4, 3), '7', Dir::Down),
+        ]
+    );
+}
+
+#[test]
+fn area_agrees_with_pick() {
+    let input = include_str!("in2.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let maze = Maze::parse(&lines).unwrap();
+    let start_loop = maze.start_loop().unwrap();
+    assert_eq!(start_loop.len(), 2 * 7030);
+    assert_eq!(start_loop.inside_by_pick(), 285);
+    assert_eq!(maze.enclosed_area(&start_loop), Ok(285));
+}
+
+#[test]
+fn generated_mazes() {
+    for seed in 0..40 {
+        let (width, height) = (3 + seed as usize % 17, 3 + seed as usize % 11);
+        let lines = generate(width, height, seed);
+        let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+        assert_eq!(lines.len(), height);
+        assert!(lines.iter().all(|l| l.len() == width));
+
+        let maze = Maze::parse(&lines).unwrap();
+        let start_loop = maze.start_loop().unwrap();
+        let inside = maze.enclosed_area(&start_loop).unwrap();
+        assert_eq!(run(lines.clone(), true) as usize, start_loop.len() / 2);
+        assert_eq!(run(lines, false) as u64, inside);
+    }
+    assert_eq!(generate(12, 8, 7), generate(12, 8, 7));
+}
+
+#[test]
+fn ambiguous_start_pipes() {
+    let lines = ["..F7", "..||", ".FSJ", ".LJ."];
+    let maze = Maze::parse(&lines).unwrap();
+    assert_eq!(
+        maze.start_pipes(),
+        Ok(vec![
+            StartPipe {
+                pipe: 'L',
+                length: 6,
+                inside: 0
+            },
+            StartPipe {
+                pipe: '7',
+                length: 4,
+                inside: 0
+            },
+        ])
+    );
+    assert_eq!(maze.start_loop(), None);
+}
+
+#[test]
+fn start_with_three_neighbors() {
+    // the pipe left of the start points at it, but doesn't lead back
+    let lines = [".....", "-S-7.", ".|.|.", ".L-J."];
+    let maze = Maze::parse(&lines).unwrap();
+    let pipes = maze.start_pipes().unwrap();
+    assert_eq!(pipes.len(), 1);
+    assert_eq!(pipes[0].pipe, 'F');
+    assert_eq!(maze.start_loop().unwrap().len(), 8);
+    assert_eq!(run(lines.to_vec(), true), 4);
+    assert_eq!(run(lines.to_vec(), false), 1);
+}
+
+#[test]
+fn start_on_the_edge() {
+    let lines = ["S7", "LJ"];
+    assert_eq!(run(lines.to_vec(), true), 2);
+    let maze = Maze::parse(&lines).unwrap();
+    assert_eq!(maze.start_pipes().unwrap()[0].pipe, 'F');
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1b1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d10"), &snapshots(lines));
+}
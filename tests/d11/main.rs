@@ -1,4 +1,7 @@
-use advent23::d11::run;
+use advent23::{
+    d11::{Expansion, Universe, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -24,3 +27,66 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), 1_000_000), 553083047914);
 }
+
+#[test]
+fn pair_distances() {
+    let input = include_str!("in1.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let universe = Universe::new(&lines, &Expansion::Uniform(2)).unwrap();
+
+    // the numbers from the puzzle description start at 1
+    assert_eq!(universe.distance(4, 8), 9);
+    assert_eq!(universe.distance(0, 6), 15);
+    assert_eq!(universe.distance(2, 5), 17);
+    assert_eq!(universe.distance(7, 8), 5);
+    assert_eq!(universe.nearest(7, 2), vec![(8, 5), (4, 6)]);
+    assert_eq!(universe.nearest(7, 100).len(), 8);
+}
+
+#[test]
+fn expansion_per_axis() {
+    let input = include_str!("in1.txt");
+    let lines: Vec<&str> = input.lines().collect();
+
+    let brute_force = |universe: &Universe| -> u64 {
+        let n = universe.galaxies().len();
+        (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .map(|(a, b)| universe.distance(a, b))
+            .sum()
+    };
+
+    let per_axis = Universe::new(&lines, &Expansion::PerAxis { rows: 1, cols: 10 }).unwrap();
+    assert_eq!(per_axis.total_distance(), brute_force(&per_axis));
+    let rows = Universe::new(&lines, &Expansion::PerAxis { rows: 10, cols: 1 }).unwrap();
+    let none = Universe::new(&lines, &Expansion::Uniform(1)).unwrap();
+    // expanding rows and columns adds to the distances independently
+    assert_ne!(per_axis.total_distance(), rows.total_distance());
+    assert_eq!(
+        per_axis.total_distance() + rows.total_distance(),
+        none.total_distance() + run(lines.clone(), 10)
+    );
+
+    let weights = Expansion::Weights {
+        rows: (1..=10).collect(),
+        cols: vec![1; 10],
+    };
+    let weighted = Universe::new(&lines, &weights).unwrap();
+    assert_eq!(weighted.total_distance(), brute_force(&weighted));
+    // rows 0 to 9 are 1 to 10 high, so galaxy 1 in row 0 and galaxy 3 in row 2 are 1 + 2 apart
+    assert_eq!(weighted.distance(0, 2), 3 + 3);
+
+    let short = Expansion::Weights {
+        rows: vec![1; 3],
+        cols: vec![1; 10],
+    };
+    assert!(Universe::new(&lines, &short).is_err());
+    assert!(Universe::new(&["#.", "..#"], &Expansion::Uniform(2)).is_err());
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d11"), &snapshots(lines));
+}
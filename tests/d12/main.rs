@@ -1,4 +1,8 @@
-use advent23::d12::run;
+use advent23::{
+    d12::{Nonogram, Row, Solutions, run, snapshots, validate},
+    rng::Rng,
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +27,100 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 18716325559999);
 }
+
+#[test]
+fn lists_arrangements_in_order() {
+    let row = Row::new("?###????????", &[3, 2, 1]).unwrap();
+    assert_eq!(row.count(), 10);
+    let all: Vec<String> = row.arrangements().collect();
+    assert_eq!(all.len(), 10);
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(all[0], ".###.##.#...");
+    assert_eq!(all[9], ".###....##.#");
+    assert_eq!(row.nth_arrangement(3), Some(all[3].clone()));
+    assert_eq!(row.nth_arrangement(10), None);
+
+    let row = Row::parse("???.### 1,1,3", 5).unwrap();
+    assert_eq!(row.count(), 1);
+    assert_eq!(row.arrangements().count(), 1);
+    assert!(Row::new("#.#", &[3]).unwrap().arrangements().next().is_none());
+}
+
+#[test]
+fn damaged_counts_match_the_arrangements() {
+    let input = include_str!("in2.txt");
+    for line in input.lines().take(50) {
+        let row = Row::parse(line, 1).unwrap();
+        let mut expected = vec![0; line.split_once(' ').unwrap().0.len()];
+        for arrangement in row.arrangements() {
+            for (i, c) in arrangement.chars().enumerate() {
+                if c == '#' {
+                    expected[i] += 1;
+                }
+            }
+        }
+        assert_eq!(row.damaged_counts(), expected, "{}", line);
+    }
+}
+
+#[test]
+fn samples_every_arrangement() {
+    let row = Row::new("?###????????", &[3, 2, 1]).unwrap();
+    let mut rng = Rng::new(12);
+    let mut seen = vec![0; 10];
+    for _ in 0..1000 {
+        let sample = row.sample(&mut rng).unwrap();
+        let k = row.arrangements().position(|a| a == sample).unwrap();
+        seen[k] += 1;
+    }
+    assert!(seen.iter().all(|&n| n > 60 && n < 140), "{:?}", seen);
+    assert_eq!(Row::new("#", &[2]).unwrap().sample(&mut rng), None);
+}
+
+#[test]
+fn nonogram_with_one_solution() {
+    let rows = vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]];
+    let cols = vec![vec![2], vec![4], vec![4], vec![4], vec![2]];
+    let nonogram = Nonogram::new(rows, cols).unwrap();
+    let expected = [".#.#. 1,1", "##### 5", "##### 5", ".###. 3", "..#.. 1"];
+    assert_eq!(nonogram.solve(), Solutions::Unique(expected.map(String::from).to_vec()));
+
+    // every line of the solution is a row with exactly one arrangement
+    let lines: Vec<&str> = expected.to_vec();
+    assert_eq!(run(lines, true), 5);
+}
+
+#[test]
+fn nonogram_that_needs_guessing() {
+    // line solving alone can't tell the two diagonals apart
+    let nonogram = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]).unwrap();
+    let Solutions::Many(first, second) = nonogram.solve() else {
+        panic!("expected two solutions");
+    };
+    assert_eq!(first, vec!["#. 1", ".# 1"]);
+    assert_eq!(second, vec![".# 1", "#. 1"]);
+
+    let rows = vec![vec![1, 1], vec![1], vec![1, 1], vec![1]];
+    let cols = vec![vec![1], vec![1, 1], vec![1], vec![1, 1]];
+    assert!(matches!(Nonogram::new(rows, cols).unwrap().solve(), Solutions::Many(..)));
+}
+
+#[test]
+fn nonogram_without_solutions() {
+    let nonogram = Nonogram::new(vec![vec![2]], vec![vec![], vec![1]]).unwrap();
+    assert_eq!(nonogram.solve(), Solutions::None);
+    let nonogram = Nonogram::new(vec![vec![1], vec![]], vec![vec![2]]).unwrap();
+    assert_eq!(nonogram.solve(), Solutions::None);
+    assert!(Nonogram::new(vec![vec![0]], vec![vec![1]]).is_err());
+    assert_eq!(run(vec!["... "], true), 1);
+    assert!(validate(&["#.# 1,,1"]).is_err());
+    assert!(validate(&["#.# 1,1,"]).is_err());
+    assert_eq!(Row::parse("..? ", 5).unwrap().count(), 1);
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d12"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d13::run;
+use advent23::{
+    d13::{Axis, Board, Reflection, run, snapshots, validate},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,80 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 30844);
 }
+
+#[test]
+fn smudges_from_the_puzzle() {
+    let input = include_str!("in1.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let mut blocks = lines.split(|line| line.is_empty());
+
+    let first = Board::parse(blocks.next().unwrap()).unwrap();
+    assert_eq!(
+        first.reflections(1, false),
+        vec![Reflection {
+            axis: Axis::Horizontal(3),
+            smudges: vec![((0, 0), (0, 5))]
+        }]
+    );
+    let second = Board::parse(blocks.next().unwrap()).unwrap();
+    assert_eq!(
+        second.reflections(1, false),
+        vec![Reflection {
+            axis: Axis::Horizontal(1),
+            smudges: vec![((4, 0), (4, 1))]
+        }]
+    );
+}
+
+#[test]
+fn any_number_of_smudges() {
+    let board = Board::parse(&["#..#", "....", "#.##"]).unwrap();
+    let axes = |k| -> Vec<Axis> { board.reflections(k, false).iter().map(|r| r.axis).collect() };
+    assert_eq!(axes(0), vec![]);
+    assert_eq!(axes(1), vec![Axis::Vertical(2), Axis::Vertical(3)]);
+    assert_eq!(axes(2), vec![Axis::Horizontal(1), Axis::Vertical(1)]);
+    assert_eq!(axes(3), vec![Axis::Horizontal(2)]);
+}
+
+#[test]
+fn diagonal_reflections() {
+    let board = Board::parse(&["#..", "##.", "#.#"]).unwrap();
+    assert_eq!(board.reflections(0, true), vec![]);
+    let found = board.reflections(2, true);
+    let diagonal = found.iter().find(|r| r.axis == Axis::Diagonal).unwrap();
+    assert_eq!(diagonal.smudges, vec![((0, 1), (1, 0)), ((0, 2), (2, 0))]);
+
+    let board = Board::parse(&["##.", "#..", "..."]).unwrap();
+    let axes: Vec<Axis> = board.reflections(0, true).iter().map(|r| r.axis).collect();
+    assert_eq!(axes, vec![Axis::Diagonal]);
+    let board = Board::parse(&[".##", "..#", "..."]).unwrap();
+    let axes: Vec<Axis> = board.reflections(0, true).iter().map(|r| r.axis).collect();
+    assert_eq!(axes, vec![Axis::AntiDiagonal]);
+    // not square
+    assert_eq!(Board::parse(&["##.", "#.."]).unwrap().reflections(0, true), vec![]);
+    assert!(Board::parse(&["#O.", "#.."]).is_err());
+}
+
+#[test]
+fn validates_single_patterns_and_trailing_blank_lines() {
+    let input = include_str!("in1.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let first = lines.split(|line| line.is_empty()).next().unwrap();
+    assert_eq!(validate(first), Ok(()));
+
+    let mut trailing = lines.clone();
+    trailing.extend(["", ""]);
+    assert_eq!(validate(&trailing), Ok(()));
+    assert_eq!(run(trailing, true), 405);
+
+    assert!(validate(&["#.", "", "", "#."]).is_err());
+    assert!(validate(&["", ""]).is_err());
+    assert!(validate(&["#.", ".."]).is_err());
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d13"), &snapshots(lines));
+}
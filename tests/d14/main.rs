@@ -1,4 +1,7 @@
-use advent23::d14::run;
+use advent23::{
+    d14::{run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,10 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 86069);
 }
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d14"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d2::run;
+use advent23::{
+    d2::{CubeGames, CubeSet, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 
 #[test]
@@ -24,3 +27,58 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 70768);
 }
+
+#[test]
+fn any_colors() {
+    let lines = [
+        "Game 1: 3 cyan, 1 magenta; 2 yellow",
+        "Game 2: 1 cyan; 4 magenta, 1 black",
+        "Game 3: 5 yellow",
+    ];
+    let cube_games = CubeGames::parse(&lines).unwrap();
+    assert_eq!(cube_games.colors, vec!["black", "cyan", "magenta", "yellow"]);
+
+    let bag = CubeSet::new(&[("cyan", 3), ("magenta", 3), ("yellow", 4)]);
+    assert!(cube_games.games[0].possible(&bag));
+    assert!(!cube_games.games[1].possible(&bag));
+    assert_eq!(cube_games.violations(&bag), vec![(2, vec![1]), (3, vec![0])]);
+
+    assert_eq!(
+        cube_games.games[0].min_bag(),
+        CubeSet::new(&[("cyan", 3), ("magenta", 1), ("yellow", 2)])
+    );
+    assert_eq!(
+        cube_games.min_bag_for(&[1, 2]),
+        Ok(CubeSet::new(&[("black", 1), ("cyan", 3), ("magenta", 4), ("yellow", 2)]))
+    );
+    assert!(cube_games.min_bag_for(&[4]).is_err());
+}
+
+#[test]
+fn zero_counts_are_like_missing_colours() {
+    assert_eq!(CubeSet::new(&[("red", 0)]), CubeSet::default());
+    assert_eq!(
+        CubeSet::new(&[("red", 0), ("blue", 2)]),
+        CubeSet::new(&[("blue", 2), ("green", 0)])
+    );
+    assert_ne!(CubeSet::new(&[("red", 1)]), CubeSet::default());
+
+    let cube_games = CubeGames::parse(&["Game 1: 0 red, 2 blue"]).unwrap();
+    assert_eq!(cube_games.games[0].min_bag(), CubeSet::new(&[("blue", 2)]));
+}
+
+#[test]
+fn min_bag_for_all_small_games() {
+    let input = include_str!("in1.txt");
+    let cube_games = CubeGames::parse(&input.lines().collect::<Vec<_>>()).unwrap();
+    let bag = cube_games.min_bag_for(&[1, 2, 3, 4, 5]).unwrap();
+    assert_eq!(bag, CubeSet::new(&[("red", 20), ("green", 13), ("blue", 15)]));
+    assert!(cube_games.violations(&bag).is_empty());
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d2"), &snapshots(lines));
+}
@@ -1,4 +1,10 @@
-use advent23::d20::run;
+use std::time::Duration;
+
+use advent23::{
+    budget::{Budget, TimedOut},
+    d20::{run, run_with_budget, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small_1() {
@@ -24,6 +30,20 @@ fn part_2_big() {
     assert_eq!(run(input.lines().collect(), false), 240162699605221);
 }
 
+#[test]
+fn part_2_big_times_out() {
+    let budget = Budget::unlimited().with_timeout(Duration::ZERO);
+    let res = run_with_budget(include_str!("in2.txt").lines().collect(), false, &budget);
+    assert_eq!(res, Err(TimedOut { partial: 0 }));
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1_2.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d20"), &snapshots(lines));
+}
+
 // Idea: could you express this problem as "topological sorting"?
 //  When a signal is emitted from a module the recipients are added to the queue in order, which means that one is < the next one.
 //  A conjunction acts on previously recieved signals, which implies that those signals are < the conjunction and all future signals from the same emitter.
@@ -1,4 +1,7 @@
-use advent23::d21::run;
+use advent23::{
+    d21::{run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 const IN_1: &str = include_str!("in1.txt");
 const IN_2: &str = include_str!("in2.txt");
@@ -52,3 +55,9 @@ fn part_2_small_5000() {
 fn part_2_big() {
     assert_eq!(run(IN_2.lines().collect(), 26501365, true), 597102953699891);
 }
+
+#[test]
+fn snapshots_small() {
+    let lines = IN_1.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d21"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d22::run;
+use advent23::{
+    d22::{run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 const IN_1: &str = include_str!("in1.txt");
 const IN_2: &str = include_str!("in2.txt");
@@ -23,3 +26,9 @@ fn part_2_big() {
     let res = run(IN_2.lines().collect(), true);
     assert_eq!(res, 64714);
 }
+
+#[test]
+fn snapshots_small() {
+    let lines = IN_1.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d22"), &snapshots(lines));
+}
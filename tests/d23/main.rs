@@ -1,4 +1,8 @@
-use advent23::d23::run;
+use advent23::{
+    budget::Budget,
+    d23::{run, run_with_budget, snapshots},
+    snapshot::assert_snapshots,
+};
 
 const IN_1: &str = include_str!("in1.txt");
 const IN_2: &str = include_str!("in2.txt");
@@ -23,3 +27,18 @@ fn part_2_big() {
     // this runs in about 0.17 seconds
     assert_eq!(run(IN_2.lines().collect(), true), 6526);
 }
+
+#[test]
+fn part_2_big_times_out_with_best_path_so_far() {
+    // the search stops at its second budget check, long before it has tried every path
+    let budget = Budget::unlimited().with_max_checks(1);
+    let res = run_with_budget(IN_2.lines().collect(), true, &budget);
+    let partial = res.unwrap_err().partial;
+    assert!(partial.is_some_and(|best| best > 0 && best <= 6526));
+}
+
+#[test]
+fn snapshots_small() {
+    let lines = IN_1.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d23"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d24::run;
+use advent23::{
+    d24::{run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 const IN_1: &str = include_str!("in1.txt");
 const IN_2: &str = include_str!("in2.txt");
@@ -22,3 +25,9 @@ fn part_2_small() {
 fn part_2_big() {
     assert_eq!(run(IN_2.lines().collect(), 200000000000000, 400000000000000, true), 549873212220117);
 }
+
+#[test]
+fn snapshots_small() {
+    let lines = IN_1.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d24"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d25::run;
+use advent23::{
+    d25::{run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 const IN_1: &str = include_str!("in1.txt");
 const IN_2: &str = include_str!("in2.txt");
@@ -12,3 +15,9 @@ fn part_1_small() {
 fn part_1_big() {
     assert_eq!(run(IN_2.lines().collect()), 518391);
 }
+
+#[test]
+fn snapshots_small() {
+    let lines = IN_1.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d25"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d3::run;
+use advent23::{
+    d3::{Combiner, Number, Schematic, SymbolClass, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,81 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 87263515);
 }
+
+#[test]
+fn listing_gears() {
+    let input = include_str!("in1.txt");
+    let schematic = Schematic::new(&input.lines().collect::<Vec<_>>());
+
+    let gears = schematic.gears(SymbolClass::Only('*'), 2..=2);
+    assert_eq!(gears.len(), 2);
+    assert_eq!((gears[0].symbol.x, gears[0].symbol.y), (3, 1));
+    assert_eq!(
+        gears[0].numbers,
+        vec![
+            Number {
+                x: 0,
+                y: 0,
+                digits: 3,
+                value: 467
+            },
+            Number {
+                x: 2,
+                y: 2,
+                digits: 2,
+                value: 35
+            },
+        ]
+    );
+
+    let lonely = schematic.gears(SymbolClass::Only('*'), 1..=1);
+    assert_eq!(lonely.len(), 1);
+    assert_eq!(lonely[0].numbers[0].value, 617);
+}
+
+#[test]
+fn other_rules() {
+    let input = include_str!("in1.txt");
+    let schematic = Schematic::new(&input.lines().collect::<Vec<_>>());
+
+    let dollar_parts: Vec<u32> = schematic
+        .part_numbers(SymbolClass::Only('$'))
+        .iter()
+        .map(|n| n.value)
+        .collect();
+    assert_eq!(dollar_parts, vec![664]);
+
+    let star = SymbolClass::Only('*');
+    assert_eq!(schematic.gear_total(star, 2..=2, Combiner::Sum), 467 + 35 + 755 + 598);
+    assert_eq!(schematic.gear_total(star, 1..=2, Combiner::Max), 467 + 617 + 755);
+    assert_eq!(
+        schematic.gear_total(SymbolClass::Any, 1..=1, Combiner::Product),
+        633 + 617 + 592 + 664
+    );
+}
+
+#[test]
+fn gears_without_numbers_add_nothing() {
+    let schematic = Schematic::new(&["12*.", "...*"]);
+    let star = SymbolClass::Only('*');
+    assert_eq!(schematic.gear_total(star, 0..=1, Combiner::Product), 12);
+    assert_eq!(schematic.gear_total(star, 0..=0, Combiner::Product), 0);
+}
+
+#[test]
+fn connected_numbers() {
+    let schematic = Schematic::new(&["1.2..3", ".*.#..", "4..5.6"]);
+    let values: Vec<Vec<u32>> = schematic
+        .components()
+        .iter()
+        .map(|c| c.iter().map(|&n| schematic.numbers[n].value).collect())
+        .collect();
+    assert_eq!(values, vec![vec![1, 2, 4, 5], vec![3], vec![6]]);
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d3"), &snapshots(lines));
+}
@@ -1,4 +1,7 @@
-use advent23::d4::run;
+use advent23::{
+    d4::{CopyRule, PastEnd, Scoring, run, snapshots, total_cards, total_points},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,63 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 14624680);
 }
+
+#[test]
+fn other_scoring() {
+    let input = include_str!("in1.txt");
+    assert_eq!(total_points(input.lines(), Scoring::Linear), Ok(4 + 2 + 2 + 1));
+    let squares = Scoring::Custom(|m| (m * m) as u64);
+    assert_eq!(total_points(input.lines(), squares), Ok(16 + 4 + 4 + 1));
+}
+
+#[test]
+fn copies_past_the_end() {
+    let lines = ["Card 1: 1 2 | 1 2", "Card 2: 3 | 3"];
+    assert_eq!(
+        total_cards(lines, CopyRule::Following, PastEnd::Clamp),
+        Ok(1 + 2)
+    );
+    assert!(total_cards(lines, CopyRule::Following, PastEnd::Error).is_err());
+}
+
+#[test]
+fn copies_with_offsets() {
+    let input = include_str!("in1.txt");
+    // cards with matches only win a copy of the card two ahead
+    let rule = CopyRule::Offsets(vec![2]);
+    assert_eq!(total_cards(input.lines(), rule, PastEnd::Clamp), Ok(1 + 1 + 2 + 2 + 3 + 3));
+}
+
+#[test]
+fn cards_must_be_in_order() {
+    let lines = ["Card 1: 1 | 2", "Card 3: 1 | 2"];
+    assert!(total_cards(lines, CopyRule::Following, PastEnd::Clamp).is_err());
+}
+
+#[test]
+fn many_cards() {
+    let n = 200_000;
+    let lines: Vec<String> = (1..=n)
+        .map(|id| {
+            let have = if id + 2 <= n { "1 2" } else { "3 4" };
+            format!("Card {}: 1 2 | {}", id, have)
+        })
+        .collect();
+
+    // each card wins copies of the next two, so the counts grow like the Fibonacci numbers
+    let res = total_cards(lines.iter().map(|l| l.as_str()), CopyRule::Following, PastEnd::Error);
+    assert_eq!(res, Err("too many cards".to_string()));
+
+    let lines: Vec<String> = (1..=n)
+        .map(|id| format!("Card {}: 1 2 | {}", id, if id < n { "1" } else { "3" }))
+        .collect();
+    let res = total_cards(lines.iter().map(|l| l.as_str()), CopyRule::Following, PastEnd::Error);
+    assert_eq!(res, Ok(n as u64 * (n as u64 + 1) / 2));
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d4"), &snapshots(lines));
+}
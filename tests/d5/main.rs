@@ -1,4 +1,10 @@
-use advent23::d5::run;
+use advent23::{
+    d5::{
+        Diagnostic, Issue, Range, check_almanac, parse_almanac, parse_almanac_strict, run,
+        snapshots,
+    },
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +29,161 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 1493866);
 }
+
+#[test]
+fn composed_mapping_small() {
+    let input = include_str!("in1.txt");
+    let almanac = parse_almanac(&input.lines().collect::<Vec<_>>(), false).unwrap();
+    let f = almanac.composed();
+
+    let locations: Vec<u64> = [79, 14, 55, 13].into_iter().map(|s| f.evaluate(s)).collect();
+    assert_eq!(locations, vec![82, 43, 86, 35]);
+    assert!(f.inverse(35).contains(&13));
+    assert!(f.inverse(82).contains(&79));
+    assert_eq!(f.breakpoints()[0], 0);
+
+    // the lowest location for the seed ranges of part 2 is 46, from seed 82
+    let almanac = parse_almanac(&input.lines().collect::<Vec<_>>(), true).unwrap();
+    assert_eq!(almanac.seeds_mapping_to(Range::new(0, 46)), vec![]);
+    assert_eq!(almanac.seeds_mapping_to(Range::new(46, 1)), vec![Range::new(82, 1)]);
+}
+
+#[test]
+fn inverse_of_a_map_that_is_not_injective() {
+    let lines = ["seeds: 0", "", "seed-to-soil map:", "5 0 1", "5 1 1"];
+    let f = parse_almanac(&lines, false).unwrap().composed();
+    assert_eq!(f.evaluate(0), 5);
+    assert_eq!(f.evaluate(1), 5);
+    assert_eq!(f.inverse(5), vec![0, 1, 5]);
+    assert_eq!(f.inverse(6), vec![6]);
+    assert_eq!(f.inverse(0), vec![]);
+}
+
+#[test]
+fn composed_mapping_agrees_with_ranges() {
+    let input = include_str!("in2.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let almanac = parse_almanac(&lines, true).unwrap();
+    let f = almanac.composed();
+
+    let lowest = almanac
+        .seeds()
+        .iter()
+        .filter_map(|&seeds| f.min_over(seeds))
+        .min();
+    assert_eq!(lowest, Some(1493866));
+
+    for &seeds in almanac.seeds() {
+        for seed in [seeds.start, seeds.start + seeds.length / 2, seeds.start + seeds.length - 1] {
+            let location = f.evaluate(seed);
+            assert!(f.inverse(location).contains(&seed));
+            assert!(!almanac.seeds_mapping_to(Range::new(location, 1)).is_empty());
+        }
+    }
+}
+
+#[test]
+fn puzzle_maps_are_bijections() {
+    for input in [include_str!("in1.txt"), include_str!("in2.txt")] {
+        let lines: Vec<&str> = input.lines().collect();
+        assert!(parse_almanac_strict(&lines, true).is_ok());
+        assert!(check_almanac(&lines).unwrap().iter().all(|d| !d.issue.is_error()));
+    }
+}
+
+#[test]
+fn diagnosing_broken_maps() {
+    let lines = [
+        "seeds: 1 2",
+        "",
+        "a-to-b map:",
+        "100 10 10",
+        "200 15 10",
+        "",
+        "b-to-c map:",
+        "0 0 5",
+        "3 5 5",
+        "12 10 3",
+    ];
+
+    let diagnostic = |section: &str, issue| Diagnostic {
+        section: section.to_string(),
+        issue,
+    };
+    assert_eq!(
+        check_almanac(&lines),
+        Ok(vec![
+            diagnostic(
+                "a-to-b",
+                Issue::OverlappingSources {
+                    lines: (4, 5),
+                    overlap: Range::new(15, 5)
+                }
+            ),
+            diagnostic(
+                "a-to-b",
+                Issue::NotBijective {
+                    line: 4,
+                    overlap: Range::new(100, 10)
+                }
+            ),
+            diagnostic(
+                "a-to-b",
+                Issue::NotBijective {
+                    line: 5,
+                    overlap: Range::new(205, 5)
+                }
+            ),
+            diagnostic("a-to-b", Issue::Gap { range: Range::new(0, 10) }),
+            diagnostic(
+                "b-to-c",
+                Issue::CollidingDestinations {
+                    lines: (8, 9),
+                    overlap: Range::new(3, 2)
+                }
+            ),
+            diagnostic(
+                "b-to-c",
+                Issue::NotBijective {
+                    line: 10,
+                    overlap: Range::new(13, 2)
+                }
+            ),
+        ])
+    );
+
+    let err = parse_almanac_strict(&lines, false).unwrap_err();
+    assert_eq!(err.lines().count(), 5);
+    assert!(err.starts_with("a-to-b: lines 4 and 5 have overlapping sources [15, 20)"));
+    assert!(parse_almanac(&lines, false).is_ok());
+}
+
+#[test]
+fn overlapping_sources_are_reported_once() {
+    // line 5 is only used for 10..15, which maps onto 60..65 and doesn't collide with line 4
+    let lines = ["seeds: 1", "", "a-to-b map:", "50 0 10", "55 5 10", "0 50 15"];
+    let issues: Vec<Issue> = check_almanac(&lines)
+        .unwrap()
+        .into_iter()
+        .map(|d| d.issue)
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            Issue::OverlappingSources {
+                lines: (4, 5),
+                overlap: Range::new(5, 5)
+            },
+            Issue::Gap {
+                range: Range::new(15, 35)
+            },
+        ]
+    );
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d5"), &snapshots(lines));
+}
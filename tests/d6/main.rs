@@ -1,4 +1,7 @@
-use advent23::d6::run;
+use advent23::{
+    d6::{Motion, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,74 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 23632299);
 }
+
+#[test]
+fn record_exactly_reachable() {
+    let motion = Motion::default();
+    // 10 * (20 - 10) = 100 ties the record, which doesn't count
+    assert_eq!(motion.good_choices(20, 100), Ok(0));
+    assert_eq!(motion.good_choices(20, 99), Ok(1));
+    assert_eq!(motion.good_choices(30, 200), Ok(9));
+}
+
+#[test]
+fn huge_races() {
+    let motion = Motion::default();
+    let t: u64 = 4_000_000_000;
+    // the record is beaten by every charge time except the first and last 1000
+    let r = 1000 * (t - 1000);
+    assert_eq!(motion.good_choices(t, r), Ok(t + 1 - 2 * 1001));
+    assert_eq!(motion.good_choices(u64::MAX, 0), Ok(u64::MAX - 1));
+    assert!(Motion { acceleration: u64::MAX, ..motion }.good_choices(u64::MAX, 1).is_err());
+}
+
+#[test]
+fn same_as_simulation() {
+    let motions = [
+        Motion::default(),
+        Motion {
+            acceleration: 3,
+            ..Motion::default()
+        },
+        Motion {
+            speed_cap: Some(7),
+            ..Motion::default()
+        },
+        Motion {
+            acceleration: 2,
+            speed_cap: Some(9),
+            penalty: 3,
+        },
+        Motion {
+            acceleration: 0,
+            speed_cap: Some(0),
+            penalty: 0,
+        },
+        Motion {
+            acceleration: 5,
+            speed_cap: Some(1),
+            penalty: 50,
+        },
+    ];
+    for motion in motions {
+        for t in 0..60 {
+            for r in (0..400).step_by(7) {
+                assert_eq!(
+                    motion.good_choices(t, r),
+                    Ok(motion.good_choices_simulated(t, r)),
+                    "{:?} time {} record {}",
+                    motion,
+                    t,
+                    r
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d6"), &snapshots(lines));
+}
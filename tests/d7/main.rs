@@ -1,4 +1,11 @@
-use advent23::d7::run;
+use std::cmp::Ordering;
+
+use advent23::{
+    d7::{
+        Category, Rules, TieBreak, explain, rank_changes, run, run_with_rules, snapshots,
+    },
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +30,154 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 245576185);
 }
+
+fn compare(rules: &Rules, a: &str, b: &str) -> Ordering {
+    let key = |h| rules.key(&rules.parse_cards(h).unwrap());
+    key(a).cmp(&key(b))
+}
+
+#[test]
+fn poker_hands() {
+    let rules = Rules::poker();
+
+    // known results from real games
+    assert_eq!(compare(&rules, "5H 5C 6S 7S KD", "2C 3S 8S 8D TD"), Ordering::Less);
+    assert_eq!(compare(&rules, "5D 8C 9S JS AC", "2C 5C 7D 8S QH"), Ordering::Greater);
+    assert_eq!(compare(&rules, "2D 9C AS AH AC", "3D 6D 7D TD QD"), Ordering::Less);
+    assert_eq!(compare(&rules, "4D 6S 9H QH QC", "3D 6D 7H QD QS"), Ordering::Greater);
+    assert_eq!(compare(&rules, "2H 2D 4C 4D 4S", "3C 3D 3S 9S 9D"), Ordering::Greater);
+    assert_eq!(compare(&rules, "2H 3D 4C 5D 6S", "6C 5H 4S 3H 2C"), Ordering::Equal);
+}
+
+#[test]
+fn poker_categories() {
+    use Category::*;
+
+    let rules = Rules::poker();
+    let ranked = [
+        ("7C 5D 4H 3S 2C", HighCard),
+        ("7C 7D 4H 3S 2C", OnePair),
+        ("7C 7D 4H 4S 2C", TwoPairs),
+        ("7C 7D 7H 4S 2C", ThreeOfAKind),
+        ("AC 2D 3H 4S 5C", Straight),
+        ("2D 3H 4S 5C 6C", Straight),
+        ("TD JH QS KC AC", Straight),
+        ("2H 4H 6H 8H TH", Flush),
+        ("7C 7D 7H 4S 4C", FullHouse),
+        ("7C 7D 7H 7S 2C", FourOfAKind),
+        ("AH 2H 3H 4H 5H", StraightFlush),
+        ("TS JS QS KS AS", StraightFlush),
+    ];
+    for (i, &(hand, category)) in ranked.iter().enumerate() {
+        assert_eq!(rules.category(&rules.parse_cards(hand).unwrap()), category, "{}", hand);
+        for &(other, _) in ranked[(i + 1)..].iter() {
+            assert_eq!(compare(&rules, hand, other), Ordering::Less, "{} < {}", hand, other);
+        }
+    }
+
+    // the ace can only be at either end of a straight
+    let wrapped = rules.parse_cards("QC KD AH 2S 3C").unwrap();
+    assert_eq!(rules.category(&wrapped), HighCard);
+}
+
+#[test]
+fn several_jokers() {
+    let rules = Rules {
+        card_order: "*J23456789TQKA".chars().collect(),
+        wildcards: vec!['*', 'J'],
+        ..Rules::camel_cards()
+    };
+    let category = |h| rules.category(&rules.parse_cards(h).unwrap());
+    assert_eq!(category("*JTTT"), Category::FiveOfAKind);
+    assert_eq!(category("*J2TT"), Category::FourOfAKind);
+    assert_eq!(category("*J234"), Category::ThreeOfAKind);
+    assert_eq!(compare(&rules, "*2222", "J2222"), Ordering::Less);
+
+    let lines = ["*J2TT 1", "22223 10", "JJJJJ 100"];
+    assert_eq!(run_with_rules(&lines, &rules), Ok(1 + 2 * 10 + 3 * 100));
+}
+
+#[test]
+fn wild_poker_and_small_hands() {
+    let rules = Rules {
+        card_order: "23456789TJQKA*".chars().collect(),
+        wildcards: vec!['*'],
+        categories: [Rules::poker().categories, vec![Category::FiveOfAKind]].concat(),
+        ..Rules::poker()
+    };
+    let category = |h| rules.category(&rules.parse_cards(h).unwrap());
+    assert_eq!(category("*S 9H TH JH QH"), Category::StraightFlush);
+    assert_eq!(category("*S 9H TH JH 2H"), Category::Flush);
+    assert_eq!(category("*S *H 7C 7D 7H"), Category::FiveOfAKind);
+    assert_eq!(compare(&rules, "*S 9H TH JH QH", "9S TS JS QS KS"), Ordering::Equal);
+
+    let three = Rules {
+        hand_size: 3,
+        tie_break: TieBreak::CardOrder,
+        ..Rules::camel_cards()
+    };
+    let category = |h| three.category(&three.parse_cards(h).unwrap());
+    assert_eq!(category("KKK"), Category::ThreeOfAKind);
+    assert_eq!(category("KK2"), Category::OnePair);
+    assert!(three.parse_cards("KKKK").is_err());
+}
+
+#[test]
+fn explaining_joker_hands() {
+    let input = include_str!("in2.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let jokers = Rules::camel_cards_with_jokers();
+    let explained = explain(&lines, &jokers).unwrap();
+
+    let winnings: u64 = explained.iter().map(|e| e.rank as u64 * e.bid).sum();
+    assert_eq!(winnings, run(lines.clone(), false));
+
+    // the substituted hands reach the same category without any jokers
+    let no_jokers = Rules {
+        wildcards: Vec::new(),
+        ..jokers.clone()
+    };
+    for e in explained.iter() {
+        assert!(!e.substituted.contains('J'), "{:?}", e);
+        let cards = no_jokers.parse_cards(&e.substituted).unwrap();
+        assert_eq!(no_jokers.category(&cards), e.category, "{:?}", e);
+    }
+
+    let mut ranks: Vec<usize> = explained.iter().map(|e| e.rank).collect();
+    ranks.sort();
+    assert_eq!(ranks, (1..=lines.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn auditing_rank_changes() {
+    let input = include_str!("in2.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let changes = rank_changes(&lines, &Rules::camel_cards(), &Rules::camel_cards_with_jokers());
+
+    let total: i64 = changes.unwrap().iter().map(|c| c.winnings_change()).sum();
+    let before = run(lines.clone(), true) as i64;
+    let after = run(lines.clone(), false) as i64;
+    assert_eq!(total, after - before);
+}
+
+#[test]
+fn substituting_wild_poker_cards() {
+    let rules = Rules {
+        card_order: "23456789TJQKA*".chars().collect(),
+        wildcards: vec!['*'],
+        ..Rules::poker()
+    };
+    let substituted = |h| rules.format_cards(&rules.substitute(&rules.parse_cards(h).unwrap()));
+    assert_eq!(substituted("*S 9H TH JH QH"), "KH9HTHJHQH");
+    assert_eq!(substituted("*S 2H 3H 4H 5D"), "6S2H3H4H5D");
+    assert_eq!(substituted("*S 2H 3H 4H 9H"), "AH2H3H4H9H");
+    assert_eq!(substituted("*S *C 7H 7D 2C"), "7S7C7H7D2C");
+    assert_eq!(substituted("*S *C *H *D *C"), "TSJSQSKSAS");
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d7"), &snapshots(lines));
+}
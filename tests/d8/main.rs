@@ -1,4 +1,7 @@
-use advent23::d8::run;
+use advent23::{
+    d8::{Cycle, Irregularity, Network, NodeSet, crt, ghost_alignment, run, snapshots},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small_1() {
@@ -29,3 +32,151 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 15690466351717);
 }
+
+#[test]
+fn crt_handles_moduli_that_are_not_coprime() {
+    assert_eq!(crt(2, 4, 4, 6), Some((10, 12)));
+    assert_eq!(crt(1, 4, 2, 6), None);
+    assert_eq!(crt(3, 5, 2, 7), Some((23, 35)));
+    assert_eq!(crt(0, 1, 5, 9), Some((5, 9)));
+}
+
+#[test]
+fn intersecting_cycles_keeps_transient_steps() {
+    let once = Cycle {
+        transient: vec![1, 3],
+        offsets: vec![],
+        start: 4,
+        length: 1,
+    };
+    let odd = Cycle {
+        transient: vec![],
+        offsets: vec![1],
+        start: 0,
+        length: 2,
+    };
+    let both = once.intersect(&odd).unwrap();
+    assert_eq!(both.transient, vec![1, 3]);
+    assert!(both.offsets.is_empty());
+    assert_eq!(both.first(), Some(1));
+}
+
+#[test]
+fn malformed_cycles_contain_nothing_after_the_start() {
+    let empty = Cycle {
+        transient: vec![1],
+        offsets: vec![5],
+        start: 3,
+        length: 0,
+    };
+    assert!(empty.contains(1));
+    assert!(!empty.contains(5));
+    assert!(empty.intersect(&empty).is_err());
+
+    let early = Cycle {
+        transient: vec![],
+        offsets: vec![1, 4],
+        start: 3,
+        length: 2,
+    };
+    assert!(!early.contains(3));
+    assert!(early.contains(6));
+}
+
+#[test]
+fn ghosts_that_never_align() {
+    let odd = ["L", "", "11A = (11Z, 11Z)", "11Z = (11A, 11A)"];
+    let even = ["22A = (22B, 22B)", "22B = (22Z, 22Z)", "22Z = (22B, 22B)"];
+    let once = ["33A = (33Z, 33Z)", "33Z = (33B, 33B)", "33B = (33B, 33B)"];
+
+    let lines: Vec<&str> = odd.iter().chain(even.iter()).copied().collect();
+    assert_eq!(ghost_alignment(&lines), Ok(None));
+
+    let lines: Vec<&str> = odd.iter().chain(once.iter()).copied().collect();
+    assert_eq!(ghost_alignment(&lines), Ok(Some(1)));
+
+    let lines: Vec<&str> = odd.iter().chain(even.iter()).chain(once.iter()).copied().collect();
+    assert_eq!(ghost_alignment(&lines), Ok(None));
+}
+
+#[test]
+fn node_sets() {
+    assert!(NodeSet::Suffix("Z".into()).contains("11Z"));
+    assert!(!NodeSet::Suffix("Z".into()).contains("Z11"));
+    assert!(NodeSet::Pattern("1?Z".into()).contains("11Z"));
+    assert!(NodeSet::Pattern("*B*".into()).contains("2BZ"));
+    assert!(!NodeSet::Pattern("1?Z".into()).contains("11ZZ"));
+    assert!(NodeSet::Nodes(vec!["AAA".into(), "BBB".into()]).contains("BBB"));
+    assert!(!NodeSet::node("AAA").contains("AAAA"));
+}
+
+#[test]
+fn network_queries() {
+    let input = include_str!("in1b.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let network = Network::parse(&lines).unwrap();
+
+    let goals = NodeSet::Suffix("Z".into());
+    assert_eq!(network.steps_to_goal("11A", &goals), Ok(Some(2)));
+    assert_eq!(network.steps_to_goal("22B", &goals), Ok(Some(2)));
+    assert_eq!(network.steps_to_goal("XXX", &NodeSet::node("11Z")), Ok(None));
+    assert!(network.steps_to_goal("ABC", &goals).is_err());
+
+    let starts = NodeSet::Pattern("?2A".into());
+    assert_eq!(network.alignment(&starts, &goals), Ok(Some(3)));
+    assert_eq!(network.alignment(&starts, &NodeSet::node("22Z")), Ok(Some(3)));
+    let all = NodeSet::Suffix("A".into());
+    assert_eq!(network.lost_walkers(&all, &NodeSet::node("22Z")), vec!["11A"]);
+    assert_eq!(network.alignment(&all, &NodeSet::node("22Z")), Ok(None));
+}
+
+#[test]
+fn big_input_has_one_goal_per_independent_loop() {
+    let input = include_str!("in2.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let network = Network::parse(&lines).unwrap();
+    let (starts, goals) = (NodeSet::Suffix("A".into()), NodeSet::Suffix("Z".into()));
+
+    assert_eq!(network.irregularities(&starts, &goals), vec![]);
+    for walker in network.walkers(&starts, &goals) {
+        assert_eq!(walker.goal_offsets(), [walker.cycle_length()]);
+    }
+}
+
+#[test]
+fn irregular_walkers() {
+    let input = include_str!("in1b.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let network = Network::parse(&lines).unwrap();
+    let starts = NodeSet::Suffix("A".into());
+
+    let walkers = network.walkers(&starts, &NodeSet::Pattern("22?".into()));
+    assert_eq!(walkers[1].start, "22A");
+    assert_eq!(walkers[0].visited, vec!["11A", "11B", "11Z"]);
+    assert_eq!(walkers[1].visited, vec!["22A", "22B", "22C", "22Z"]);
+    assert_eq!(walkers[1].transient_length(), 1);
+    assert_eq!(walkers[1].goal_offsets(), [1, 2, 3, 4, 5, 6]);
+
+    let found = network.irregularities(&starts, &NodeSet::Pattern("22?".into()));
+    assert_eq!(found[0], Irregularity::NoGoal { start: "11A" });
+    assert_eq!(found[1], Irregularity::TransientGoals { start: "22A", steps: vec![0] });
+    assert!(matches!(found[2], Irregularity::SeveralGoals { start: "22A", .. }));
+    assert_eq!(found.len(), 3);
+
+    let starts = NodeSet::Nodes(vec!["11A".into(), "11B".into()]);
+    let found = network.irregularities(&starts, &NodeSet::Suffix("Z".into()));
+    assert_eq!(
+        found,
+        vec![
+            Irregularity::NoGoal { start: "11B" },
+            Irregularity::SharedNode { starts: ("11A", "11B"), node: "11B" },
+        ]
+    );
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1b.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d8"), &snapshots(lines));
+}
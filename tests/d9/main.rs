@@ -1,4 +1,7 @@
-use advent23::d9::run;
+use advent23::{
+    d9::{Sequence, run, snapshots, validate},
+    snapshot::assert_snapshots,
+};
 
 #[test]
 fn part_1_small() {
@@ -23,3 +26,42 @@ fn part_2_big() {
     let input = include_str!("in2.txt");
     assert_eq!(run(input.lines().collect(), false), 977);
 }
+
+#[test]
+fn newton_coefficients() {
+    let squares = Sequence::new(&[0, 1, 4, 9, 16]).unwrap();
+    assert_eq!(squares.degree(), 2);
+    assert_eq!(squares.coefficients(), [0.into(), 1.into(), 2.into()]);
+    assert_eq!(squares.next(), 25.into());
+    assert_eq!(squares.previous(), 1.into());
+
+    let zeros = Sequence::new(&[0, 0]).unwrap();
+    assert_eq!(zeros.degree(), 0);
+    assert_eq!(zeros.at(-5), 0.into());
+}
+
+#[test]
+fn extrapolation_far_away_stays_exact() {
+    let cubes = Sequence::new(&[-8, -1, 0, 1, 8, 27]).unwrap();
+    let x: i64 = 10_000_000_000;
+    let expected = "999999999400000000119999999992".parse().unwrap();
+    // the sequence starts at -2, so index x is (x - 2)^3
+    assert_eq!(cubes.at(x), expected);
+    assert_eq!(cubes.at(-x + 4), -expected);
+}
+
+#[test]
+fn non_polynomial_sequences_are_errors() {
+    assert!(Sequence::new(&[1, 2, 4, 8, 16]).is_err());
+    assert!(Sequence::new(&[3, 5]).is_err());
+    assert!(Sequence::new(&[3, 3]).is_ok());
+    assert!(validate(&["1 2 4 8 16"]).is_err());
+    assert!(validate(&["0 3 6 9"]).is_ok());
+}
+
+#[test]
+fn snapshots_small() {
+    let input = include_str!("in1.txt");
+    let lines = input.lines().collect();
+    assert_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/d9"), &snapshots(lines));
+}
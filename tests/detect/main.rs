@@ -0,0 +1,57 @@
+use advent23::detect::{classify, detect_day};
+
+const INPUTS: [(u8, &str, &str); 25] = [
+    (1, include_str!("../d1/in1a.txt"), include_str!("../d1/in2.txt")),
+    (2, include_str!("../d2/in1.txt"), include_str!("../d2/in2.txt")),
+    (3, include_str!("../d3/in1.txt"), include_str!("../d3/in2.txt")),
+    (4, include_str!("../d4/in1.txt"), include_str!("../d4/in2.txt")),
+    (5, include_str!("../d5/in1.txt"), include_str!("../d5/in2.txt")),
+    (6, include_str!("../d6/in1.txt"), include_str!("../d6/in2.txt")),
+    (7, include_str!("../d7/in1.txt"), include_str!("../d7/in2.txt")),
+    (8, include_str!("../d8/in1b.txt"), include_str!("../d8/in2.txt")),
+    (9, include_str!("../d9/in1.txt"), include_str!("../d9/in2.txt")),
+    (10, include_str!("../d10/in1a.txt"), include_str!("../d10/in2.txt")),
+    (11, include_str!("../d11/in1.txt"), include_str!("../d11/in2.txt")),
+    (12, include_str!("../d12/in1.txt"), include_str!("../d12/in2.txt")),
+    (13, include_str!("../d13/in1.txt"), include_str!("../d13/in2.txt")),
+    (14, include_str!("../d14/in1.txt"), include_str!("../d14/in2.txt")),
+    (15, include_str!("../d15/in1.txt"), include_str!("../d15/in2.txt")),
+    (16, include_str!("../d16/in1.txt"), include_str!("../d16/in2.txt")),
+    (17, include_str!("../d17/in1.txt"), include_str!("../d17/in2.txt")),
+    (18, include_str!("../d18/in1.txt"), include_str!("../d18/in2.txt")),
+    (19, include_str!("../d19/in1.txt"), include_str!("../d19/in2.txt")),
+    (20, include_str!("../d20/in1_2.txt"), include_str!("../d20/in2.txt")),
+    (21, include_str!("../d21/in1.txt"), include_str!("../d21/in2.txt")),
+    (22, include_str!("../d22/in1.txt"), include_str!("../d22/in2.txt")),
+    (23, include_str!("../d23/in1.txt"), include_str!("../d23/in2.txt")),
+    (24, include_str!("../d24/in1.txt"), include_str!("../d24/in2.txt")),
+    (25, include_str!("../d25/in1.txt"), include_str!("../d25/in2.txt")),
+];
+
+#[test]
+fn detects_small_inputs() {
+    for (day, small, _) in INPUTS {
+        assert_eq!(detect_day(small).map(|d| d.day), Some(day), "day {}", day);
+    }
+}
+
+#[test]
+fn detects_big_inputs() {
+    for (day, _, big) in INPUTS {
+        assert_eq!(detect_day(big).map(|d| d.day), Some(day), "day {}", day);
+    }
+}
+
+#[test]
+fn confidences_add_up_to_one() {
+    let detections = classify(include_str!("../d11/in1.txt"));
+    let total: f64 = detections.iter().map(|d| d.confidence).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(detections[0].confidence > 0.5);
+}
+
+#[test]
+fn rejects_garbage() {
+    assert_eq!(classify("hello world\nthis is not a puzzle"), vec![]);
+    assert_eq!(classify(""), vec![]);
+}
@@ -0,0 +1,49 @@
+use std::{
+    fs::{self, File},
+    time::{Duration, SystemTime},
+};
+
+use advent23::{
+    runner::{solve, Part},
+    watch::Watcher,
+};
+
+#[test]
+fn solve_runs_a_day_and_rejects_bad_days() {
+    let input = include_str!("../d2/in1.txt");
+    assert_eq!(solve(2, Part::One, input), Ok("8".to_string()));
+    assert_eq!(solve(2, Part::Two, input), Ok("2286".to_string()));
+    assert!(solve(25, Part::Two, "").is_err());
+    assert!(solve(26, Part::One, "").is_err());
+}
+
+#[test]
+fn solve_catches_panics() {
+    assert!(solve(2, Part::One, "not a game").is_err());
+}
+
+#[test]
+fn reports_changes_when_file_is_modified() {
+    let path = std::env::temp_dir().join(format!("advent23-watch-{}.txt", std::process::id()));
+    fs::write(&path, "1abc2\n").unwrap();
+
+    let mut watcher = Watcher::new(&path, 1, vec![Part::One]);
+
+    let changes = watcher.poll().unwrap().unwrap();
+    assert_eq!(changes[0].previous, None);
+    assert_eq!(changes[0].current, Ok("12".to_string()));
+
+    assert_eq!(watcher.poll().unwrap(), None);
+
+    fs::write(&path, "3abc4\n").unwrap();
+    // make sure the timestamp changes even on file systems with coarse timestamps
+    let later = SystemTime::now() + Duration::from_secs(10);
+    File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+    let changes = watcher.poll().unwrap().unwrap();
+    assert_eq!(changes[0].previous, Some(Ok("12".to_string())));
+    assert_eq!(changes[0].current, Ok("34".to_string()));
+    assert_eq!(changes[0].to_string(), "part 1: 12 -> 34");
+
+    fs::remove_file(&path).unwrap();
+}